@@ -1,12 +1,11 @@
-#![cfg_attr(feature = "nightly", feature(custom_derive, plugin))]
-#![cfg_attr(feature = "nightly", plugin(rebind_plugins))]
-
 #[macro_use]
 extern crate conrod;
 extern crate find_folder;
 extern crate glutin_window;
 extern crate graphics;
 extern crate rebind;
+#[macro_use]
+extern crate rebind_plugins;
 extern crate piston;
 extern crate opengl_graphics;
 extern crate viewport;
@@ -18,9 +17,9 @@ use graphics::*;
 use opengl_graphics::{GlGraphics, OpenGL};
 use opengl_graphics::glyph_cache::GlyphCache;
 use piston::event_loop::{EventMap, Events};
-use piston::input::{Event, Input, Motion, RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
+use piston::input::{Event, Input, RenderArgs, RenderEvent, UpdateArgs, UpdateEvent};
 use piston::window::{Window, WindowSettings};
-use rebind::{Action, Builder, InputTranslator, Translated};
+use rebind::{Builder, InputTranslator, Translated, VirtualCursor};
 use std::cell::RefCell;
 use std::rc::Rc;
 
@@ -34,40 +33,40 @@ struct App {
     ui: RcUi,
     translator: InputTranslator<CharacterAction>,
     character: Character,
-    cursor: VirtualCursor,
     bg_color: Color
 }
 
 impl App {
     fn input(&mut self, input: &Input) {
-        if let Some(t) = self.translator.translate(input) {
-            match t {
-                Translated::Press(action) => {
-                    const CHARACTER_WALK_SPEED_INCREMENT: f64 = 400.0;
-                    match action {
-                        CharacterAction::Jump => {
-                            println!("You jumped! Yay!");
-                        }
-                        CharacterAction::MoveLeft => {
-                            if self.character.current_velocity[0] > (self.character.max_velocity[0] * -1.0) {
-                                self.character.current_velocity[0] -= CHARACTER_WALK_SPEED_INCREMENT;
-                            }
+        for t in self.translator.translate_stateful(input) {
+            self.translated(t);
+        }
+    }
+
+    fn translated(&mut self, t: Translated<CharacterAction>) {
+        match t {
+            Translated::Press(action) => {
+                const CHARACTER_WALK_SPEED_INCREMENT: f64 = 400.0;
+                match action {
+                    CharacterAction::Jump => {
+                        println!("You jumped! Yay!");
+                    }
+                    CharacterAction::MoveLeft => {
+                        if self.character.current_velocity[0] > (self.character.max_velocity[0] * -1.0) {
+                            self.character.current_velocity[0] -= CHARACTER_WALK_SPEED_INCREMENT;
                         }
-                        CharacterAction::MoveRight => {
-                            if self.character.current_velocity[0] < self.character.max_velocity[0] {
-                                self.character.current_velocity[0] += CHARACTER_WALK_SPEED_INCREMENT;
-                            }
+                    }
+                    CharacterAction::MoveRight => {
+                        if self.character.current_velocity[0] < self.character.max_velocity[0] {
+                            self.character.current_velocity[0] += CHARACTER_WALK_SPEED_INCREMENT;
                         }
                     }
                 }
-                Translated::Release(_) => {
-                    self.character.current_velocity = [0.0, 0.0];
-                }
-                Translated::Move(Motion::MouseCursor(x, y)) => {
-                    self.cursor.position = [x, y];
-                }
-                _ => {}
             }
+            Translated::Release(_) => {
+                self.character.current_velocity = [0.0, 0.0];
+            }
+            _ => {}
         }
     }
 
@@ -76,6 +75,12 @@ impl App {
         // the update event from the window events queue is currently broken.
         self.translator.set_size(self.window.borrow().size());
 
+        // Move the virtual cursor, which is driven by the mouse and the left stick of the
+        // first controller
+        for t in self.translator.update(args.dt) {
+            self.translated(t);
+        }
+
         // Update the character's velocity
         let ctl = self.character.topleft;
         let v = self.character.current_velocity;
@@ -90,6 +95,7 @@ impl App {
         // structure your application to pass the translator between separate options and game
         // screens.
         let mut rebind = self.translator.clone().into_rebind();
+        let cursor_position = self.translator.get_cursor_position();
 
         let mut gl_graphics = self.graphics.borrow_mut();
         let ui = &mut *self.ui.borrow_mut();
@@ -138,13 +144,14 @@ impl App {
         });
 
         // Draw the cursor dot
-        gl_graphics.draw(args.viewport(), |c, gl| {
-            let dot = ellipse::circle(self.cursor.position[0],
-                                      self.cursor.position[1],
-                                      self.cursor.size);
+        if let Some(position) = cursor_position {
+            const CURSOR_SIZE: f64 = 5.0;
+            gl_graphics.draw(args.viewport(), |c, gl| {
+                let dot = ellipse::circle(position[0], position[1], CURSOR_SIZE);
 
-            ellipse(self.cursor.color.to_fsa(), dot, c.transform, gl)
-        });
+                ellipse(green().to_fsa(), dot, c.transform, gl)
+            });
+        }
 
         self.translator = rebind.into();
     }
@@ -170,33 +177,13 @@ impl Character {
     }
 }
 
-struct VirtualCursor {
-    position: [f64; 2],
-    color: Color,
-    size: f64
-}
-
-impl VirtualCursor {
-    fn new() -> Self {
-        VirtualCursor {
-            position: [0.0, 0.0],
-            color: green(),
-            size: 5.0
-        }
-    }
-}
-
-#[cfg_attr(feature = "nightly", derive(Action))]
-#[cfg_attr(not(feature = "nightly"), derive(Copy, Clone, Eq, Hash, Ord, PartialEq, PartialOrd))]
+#[derive(Action)]
 enum CharacterAction {
     Jump,
     MoveLeft,
     MoveRight
 }
 
-#[cfg(not(feature = "nightly"))]
-impl Action for CharacterAction { }
-
 fn main() {
     use piston::input::keyboard::Key;
     use piston::input::Button::Keyboard;
@@ -220,6 +207,7 @@ fn main() {
                          .with_mapping(CharacterAction::MoveLeft, Keyboard(Key::A))
                          .with_mapping(CharacterAction::MoveRight, Keyboard(Key::Right))
                          .with_mapping(CharacterAction::MoveRight, Keyboard(Key::D))
+                         .virtual_cursor(Some(VirtualCursor::new().with_stick(0, 0, 1)))
                          .build_translator();
 
     let character = {
@@ -246,7 +234,6 @@ fn main() {
         ui: Rc::new(RefCell::new(ui)),
         translator: translator,
         character: character,
-        cursor: VirtualCursor::new(),
         bg_color: black()
     };
 
@@ -1,21 +1,251 @@
-#![allow(unused_variables)]
-
-use syntax::ast;
-use syntax::ast::MetaItem;
-use syntax::codemap::Span;
-use syntax::ext::base::{Annotatable, ExtCtxt};
-
-pub fn expand_derive_action_annotation(context: &mut ExtCtxt,
-                                       span: Span,
-                                       meta_item: &MetaItem,
-                                       item: &Annotatable,
-                                       push: &mut FnMut(Annotatable)) {
-    //
-    // match item.node {
-    // ast::ItemEnum(def, _) => {
-    // add definitions
-    // },
-    // _ => {context.span_err(span, "dummy is only permissiable on functions");}
-    // }
-    //
+use proc_macro::{Delimiter, Group, Literal, TokenStream, TokenTree};
+use std::iter::Peekable;
+
+/// An enum which `Action` is being derived for.
+pub struct ActionEnum {
+    pub name: String,
+    pub derive_bounds: bool,
+    pub variants: Vec<Variant>
+}
+
+/// A variant of an `ActionEnum`, and the name which `NamedAction` gives it.
+pub struct Variant {
+    pub ident: String,
+    pub name: String
+}
+
+pub fn parse_enum(input: TokenStream) -> Result<ActionEnum, String> {
+    let mut tokens = input.into_iter().peekable();
+    let mut derive_bounds = true;
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '#' => {
+                for (arg, _) in action_attribute(tokens.next())? {
+                    match &arg[..] {
+                        "no_bounds" => derive_bounds = false,
+                        _ => return Err(format!("unknown action attribute `{}`", arg)),
+                    }
+                }
+            }
+            Some(TokenTree::Ident(ref i)) if i.to_string() == "pub" => {
+                if let Some(&TokenTree::Group(_)) = tokens.peek() {
+                    tokens.next();
+                }
+            }
+            Some(TokenTree::Ident(ref i)) if i.to_string() == "enum" => break,
+            _ => return Err("derive(Action) only supports enums".to_string()),
+        }
+    }
+
+    let name = match tokens.next() {
+        Some(TokenTree::Ident(i)) => i.to_string(),
+        _ => return Err("expected the name of the enum".to_string()),
+    };
+    let body = match tokens.next() {
+        Some(TokenTree::Group(ref g)) if g.delimiter() == Delimiter::Brace => g.stream(),
+        _ => return Err(format!("derive(Action) doesn't support generic enums such as `{}`", name)),
+    };
+
+    let mut variants: Vec<Variant> = vec![];
+    let mut body = body.into_iter().peekable();
+    while body.peek().is_some() {
+        let variant = parse_variant(&mut body)?;
+        if variants.iter().any(|v| v.name == variant.name) {
+            return Err(format!("more than one variant is named \"{}\"", variant.name));
+        }
+        variants.push(variant);
+    }
+    Ok(ActionEnum {
+        name: name,
+        derive_bounds: derive_bounds,
+        variants: variants
+    })
+}
+
+fn parse_variant<I: Iterator<Item = TokenTree>>(tokens: &mut Peekable<I>) -> Result<Variant, String> {
+    let mut rename = None;
+    let ident = loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '#' => {
+                for arg in action_attribute(tokens.next())? {
+                    match arg {
+                        (ref arg, Some(ref value)) if arg == "rename" => rename = Some(value.clone()),
+                        (arg, _) => return Err(format!("unknown action attribute `{}`", arg)),
+                    }
+                }
+            }
+            Some(TokenTree::Ident(i)) => break i.to_string(),
+            _ => return Err("expected the name of a variant".to_string()),
+        }
+    };
+
+    // Fields come straight after the name, but a discriminant, which may contain groups such
+    // as `(1 << 2)`, is skipped up to the comma which ends the variant
+    if let Some(&TokenTree::Group(_)) = tokens.peek() {
+        return Err(format!("derive(Action) doesn't support variants with fields such as `{}`", ident));
+    }
+    loop {
+        match tokens.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == ',' => break,
+            None => break,
+            _ => {}
+        }
+    }
+    Ok(Variant {
+        name: rename.unwrap_or_else(|| snake_case(&ident)),
+        ident: ident
+    })
+}
+
+/// Converts a variant name such as `MoveLeft` or `OpenHUDMenu` into snake case, such as
+/// `move_left` or `open_hud_menu`.
+fn snake_case(ident: &str) -> String {
+    let chars: Vec<char> = ident.chars().collect();
+    let mut name = String::new();
+    for (i, &c) in chars.iter().enumerate() {
+        if c.is_uppercase() && i > 0 {
+            let after_lower = !chars[i - 1].is_uppercase() && chars[i - 1] != '_';
+            let before_lower = chars[i - 1].is_uppercase() && chars.get(i + 1).map_or(false, |n| n.is_lowercase());
+            if after_lower || before_lower {
+                name.push('_');
+            }
+        }
+        name.extend(c.to_lowercase());
+    }
+    name
+}
+
+/// Returns the arguments of an `#[action(...)]` attribute, as pairs of names and optional
+/// string values, such as `no_bounds` or `rename = "jump"`. Other attributes have no
+/// arguments.
+fn action_attribute(attribute: Option<TokenTree>) -> Result<Vec<(String, Option<String>)>, String> {
+    let group = match attribute {
+        Some(TokenTree::Group(ref g)) if g.delimiter() == Delimiter::Bracket => g.clone(),
+        _ => return Err("expected an attribute".to_string()),
+    };
+    let mut tokens = group.stream().into_iter();
+    match (tokens.next(), tokens.next()) {
+        (Some(TokenTree::Ident(ref i)), Some(TokenTree::Group(ref args))) if i.to_string() == "action" => {
+            parse_arguments(args)
+        }
+        _ => Ok(vec![]),
+    }
+}
+
+fn parse_arguments(args: &Group) -> Result<Vec<(String, Option<String>)>, String> {
+    let mut parsed = vec![];
+    let mut tokens = args.stream().into_iter().peekable();
+    while let Some(token) = tokens.next() {
+        let name = match token {
+            TokenTree::Ident(i) => i.to_string(),
+            t => return Err(format!("unexpected `{}` in action attribute", t)),
+        };
+        let value = match tokens.next() {
+            Some(TokenTree::Punct(ref p)) if p.as_char() == '=' => {
+                match tokens.next() {
+                    Some(TokenTree::Literal(ref l)) => {
+                        let value = Some(string_value(&name, l)?);
+                        if let Some(TokenTree::Punct(ref p)) = tokens.next() {
+                            if p.as_char() != ',' {
+                                return Err(format!("unexpected `{}` in action attribute", p));
+                            }
+                        }
+                        value
+                    }
+                    _ => return Err(format!("expected a string value for `{}`", name)),
+                }
+            }
+            Some(TokenTree::Punct(ref p)) if p.as_char() == ',' => None,
+            None => None,
+            Some(t) => return Err(format!("unexpected `{}` in action attribute", t)),
+        };
+        parsed.push((name, value));
+    }
+    Ok(parsed)
+}
+
+/// Returns the value of a string literal such as `"jump"` or `r#"jump"#`. Strings with
+/// escapes are rejected rather than unescaped, since a name never needs them.
+fn string_value(name: &str, literal: &Literal) -> Result<String, String> {
+    let text = literal.to_string();
+    let raw = text.starts_with('r');
+    let quoted = if raw { text[1..].trim_matches('#') } else { &text[..] };
+    if quoted.len() < 2 || !quoted.starts_with('"') || !quoted.ends_with('"') {
+        return Err(format!("expected a string value for `{}`", name));
+    }
+    let value = &quoted[1..quoted.len() - 1];
+    if !raw && value.contains('\\') {
+        return Err(format!("the value of `{}` can't contain escapes, such as {}", name, text));
+    }
+    Ok(value.to_string())
+}
+
+pub fn expand(action: &ActionEnum) -> String {
+    let name = &action.name;
+    let all: String = action.variants.iter().map(|v| format!("{}::{},", name, v.ident)).collect();
+    let names: String = action.variants.iter().map(|v| format!("{}::{} => {:?},", name, v.ident, v.name)).collect();
+    let mut code = format!("
+        impl ::rebind::Action for {name} {{ }}
+
+        impl ::rebind::NamedAction for {name} {{
+            fn all() -> &'static [Self] {{
+                static ALL: &'static [{name}] = &[{all}];
+                ALL
+            }}
+
+            fn name(&self) -> &'static str {{
+                match *self {{ {names} }}
+            }}
+        }}",
+                           name = name,
+                           all = all,
+                           names = names);
+    if action.derive_bounds {
+        let index = format!("__rebind_action_index_{}", name);
+        let arms: String = action.variants
+                                 .iter()
+                                 .enumerate()
+                                 .map(|(i, v)| format!("{}::{} => {}usize,", name, v.ident, i))
+                                 .collect();
+        code.push_str(&format!("
+            #[doc(hidden)]
+            #[allow(non_snake_case)]
+            fn {index}(action: &{name}) -> usize {{
+                match *action {{ {arms} }}
+            }}
+
+            impl ::std::clone::Clone for {name} {{
+                fn clone(&self) -> Self {{ *self }}
+            }}
+
+            impl ::std::marker::Copy for {name} {{ }}
+
+            impl ::std::cmp::PartialEq for {name} {{
+                fn eq(&self, other: &Self) -> bool {{ {index}(self) == {index}(other) }}
+            }}
+
+            impl ::std::cmp::Eq for {name} {{ }}
+
+            impl ::std::cmp::PartialOrd for {name} {{
+                fn partial_cmp(&self, other: &Self) -> ::std::option::Option<::std::cmp::Ordering> {{
+                    ::std::option::Option::Some(::std::cmp::Ord::cmp(self, other))
+                }}
+            }}
+
+            impl ::std::cmp::Ord for {name} {{
+                fn cmp(&self, other: &Self) -> ::std::cmp::Ordering {{
+                    ::std::cmp::Ord::cmp(&{index}(self), &{index}(other))
+                }}
+            }}
+
+            impl ::std::hash::Hash for {name} {{
+                fn hash<H: ::std::hash::Hasher>(&self, state: &mut H) {{
+                    ::std::hash::Hash::hash(&{index}(self), state)
+                }}
+            }}",
+                               name = name,
+                               index = index,
+                               arms = arms));
+    }
+    code
 }
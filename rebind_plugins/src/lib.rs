@@ -1,47 +1,70 @@
-#![feature(rustc_private, plugin_registrar)]
 #![warn(missing_docs)]
 
 //! rebind_plugins
 //! ==============
 //!
-//! A compiler plugin which complements the `rebind` crate by providing the
-//! `#[derive(Action)]`
-//! annotation.
+//! A procedural macro crate which complements the `rebind` crate by providing the
+//! `#[derive(Action)]` annotation.
+//!
+//! Deriving `Action` for an enum whose variants don't have fields also implements the
+//! traits which `Action` requires (`Clone`, `Copy`, `PartialEq`, `Eq`, `PartialOrd`,
+//! `Ord` and `Hash`), ordering the variants as they are declared, so they shouldn't be
+//! derived as well. To derive or implement them yourself instead, add `#[action(no_bounds)]`
+//! to the enum, and the compiler will check that they are implemented.
+//!
+//! Unlike `#[derive(PartialOrd, Ord)]`, which orders variants by their discriminants, the
+//! derived order ignores explicit discriminants, so that it always matches the order of
+//! `NamedAction::all`. For an enum such as `enum E { B = 2, A = 1 }`, `E::B < E::A`. Use
+//! `#[action(no_bounds)]` and derive the traits to order by discriminant instead.
+//!
+//! It also implements `NamedAction`, which lists the variants and names them in snake case.
+//! A variant can be given a different name with `#[action(rename = "...")]`. The name can be
+//! a raw string, but can't contain escapes.
 //!
 //! Example
 //! -------
 //!
 //! ```
-//! #![feature(plugin)]
-//! #![plugin(rebind_macros)]
-//!
+//! #[macro_use]
+//! extern crate rebind_plugins;
 //! extern crate rebind;
-//! use rebind::RebindBuilder;
 //!
-//! fn main {
-//!    #[derive(Action)]
-//!    enum MyAction {ActionA, ActionB}
+//! use rebind::{Builder, NamedAction};
 //!
-//!    let _ = RebindBuilder::<MyAction>::new().build_translator();
-//!    // ...
+//! #[derive(Action)]
+//! enum MyAction {
+//!     ActionA,
+//!     #[action(rename = "b")]
+//!     ActionB
+//! }
+//!
+//! #[derive(Action, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+//! #[action(no_bounds)]
+//! enum MyDebugAction {
+//!     ActionC
+//! }
+//!
+//! fn main() {
+//!     assert_eq!(MyAction::all().len(), 2);
+//!     assert_eq!(MyAction::ActionA.name(), "action_a");
+//!     assert!(MyAction::from_name("b") == Some(MyAction::ActionB));
+//!     let _ = Builder::<MyAction>::new((800, 600)).build_translator();
+//!     let _ = Builder::<MyDebugAction>::new((800, 600)).build_translator();
 //! }
 //! ```
 
-extern crate rebind;
-extern crate rustc;
-extern crate syntax;
+extern crate proc_macro;
 
 mod derive_action;
 
-use rustc::plugin::Registry;
-use syntax::parse::token::intern;
-use syntax::ext::base::SyntaxExtension;
-
-use derive_action::expand_derive_action_annotation;
+use proc_macro::TokenStream;
 
-#[plugin_registrar]
+#[proc_macro_derive(Action, attributes(action))]
 #[doc(hidden)]
-pub fn plugin_registrar(registry: &mut Registry) {
-    registry.register_syntax_extension(intern("derive_Action"),
-                                       SyntaxExtension::MultiDecorator(Box::new(expand_derive_action_annotation)));
+pub fn derive_action(input: TokenStream) -> TokenStream {
+    let code = match derive_action::parse_enum(input) {
+        Ok(action_enum) => derive_action::expand(&action_enum),
+        Err(message) => format!("compile_error!({:?});", message),
+    };
+    code.parse().expect("derive(Action) generated invalid code")
 }
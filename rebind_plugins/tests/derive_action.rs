@@ -0,0 +1,130 @@
+#![allow(dead_code)]
+
+extern crate rebind;
+extern crate input;
+#[macro_use]
+extern crate rebind_plugins;
+
+use input::Input;
+use input::Button::Keyboard;
+use input::keyboard::Key;
+use rebind::{Action, Builder, InputRebind, NamedAction, Translated};
+use std::collections::HashSet;
+
+#[derive(Action, Debug)]
+pub enum ManyActions {
+    Action0, Action1, Action2, Action3, Action4, Action5, Action6, Action7, Action8, Action9,
+    Action10, Action11, Action12, Action13, Action14, Action15, Action16, Action17, Action18, Action19,
+    Action20, Action21, Action22, Action23, Action24, Action25, Action26, Action27, Action28, Action29,
+    Action30, Action31, Action32, Action33, Action34, Action35, Action36, Action37, Action38, Action39,
+    /// Variants can have attributes
+    #[allow(unused)]
+    Action40
+}
+
+#[derive(Action, Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+#[action(no_bounds)]
+enum OwnBounds {
+    Jump,
+    Crouch
+}
+
+#[derive(Action, Debug)]
+enum Renamed {
+    MoveLeft,
+    #[action(rename = "fire")]
+    PrimaryAttack,
+    #[action(rename = r#"open_hud"#)]
+    OpenHUDMenu,
+    Zoom2x
+}
+
+#[derive(Action, Debug)]
+enum Discriminants {
+    Last = 10,
+    First = 1,
+    Middle = 5,
+    Shifted = (1 << 2),
+    Block = { 3 }
+}
+
+fn requires_action<A: Action>(a: A) -> A {
+    a
+}
+
+#[test]
+fn test_derived_bounds() {
+    let a = ManyActions::Action3;
+    let b = a;
+    assert_eq!(a, b);
+    assert!(ManyActions::Action0 < ManyActions::Action1);
+    assert!(ManyActions::Action9 < ManyActions::Action10);
+    assert!(ManyActions::Action40 > ManyActions::Action39);
+    assert_eq!(ManyActions::Action20.clone(), ManyActions::Action20);
+    assert!(ManyActions::Action20 != ManyActions::Action21);
+
+    let set: HashSet<_> = vec![ManyActions::Action0, ManyActions::Action40, ManyActions::Action0, ManyActions::Action7]
+                              .into_iter()
+                              .collect();
+    assert_eq!(set.len(), 3);
+    assert!(set.contains(&ManyActions::Action40));
+    assert!(!set.contains(&ManyActions::Action8));
+
+    assert_eq!(requires_action(OwnBounds::Crouch), OwnBounds::Crouch);
+    assert!(OwnBounds::Jump < OwnBounds::Crouch);
+}
+
+#[test]
+fn test_variants_are_ordered_by_declaration() {
+    assert!(Discriminants::Last < Discriminants::First);
+    assert!(Discriminants::First < Discriminants::Middle);
+    let mut actions = vec![Discriminants::Middle, Discriminants::First, Discriminants::Last];
+    actions.sort();
+    assert_eq!(actions, vec![Discriminants::Last, Discriminants::First, Discriminants::Middle]);
+}
+
+#[test]
+fn test_derived_actions_can_be_translated() {
+    let translator = Builder::<ManyActions>::default()
+                         .with_mapping(ManyActions::Action39, Keyboard(Key::Space))
+                         .with_mapping(ManyActions::Action2, Keyboard(Key::A))
+                         .build_translator();
+
+    assert_eq!(translator.translate(&Input::Press(Keyboard(Key::Space))),
+               Some(Translated::Press(ManyActions::Action39)));
+    assert_eq!(translator.translate(&Input::Release(Keyboard(Key::A))),
+               Some(Translated::Release(ManyActions::Action2)));
+    assert_eq!(translator.translate(&Input::Press(Keyboard(Key::B))), None);
+}
+
+#[test]
+fn test_action_names() {
+    assert_eq!(ManyActions::all().len(), 41);
+    assert_eq!(ManyActions::all()[40], ManyActions::Action40);
+    assert_eq!(ManyActions::Action12.name(), "action12");
+    assert_eq!(OwnBounds::all(), &[OwnBounds::Jump, OwnBounds::Crouch]);
+    assert_eq!(Discriminants::all(),
+               &[Discriminants::Last, Discriminants::First, Discriminants::Middle, Discriminants::Shifted,
+                 Discriminants::Block]);
+    assert_eq!(Discriminants::Shifted.name(), "shifted");
+
+    let names: Vec<_> = Renamed::all().iter().map(|a| a.name()).collect();
+    assert_eq!(names, vec!["move_left", "fire", "open_hud", "zoom2x"]);
+    for &action in Renamed::all() {
+        assert_eq!(Renamed::from_name(action.name()), Some(action));
+    }
+    assert_eq!(Renamed::from_name("primary_attack"), None);
+}
+
+#[test]
+fn test_unbound_actions() {
+    let mut rebind: InputRebind<Renamed> = Builder::default()
+                                               .with_mapping(Renamed::PrimaryAttack, Keyboard(Key::Space))
+                                               .into();
+    rebind.insert_action(Renamed::Zoom2x);
+    assert_eq!(rebind.get_unbound_actions(),
+               vec![Renamed::MoveLeft, Renamed::OpenHUDMenu, Renamed::Zoom2x]);
+    let bindings = rebind.get_all_bindings();
+    assert_eq!(bindings.len(), 4);
+    assert!(bindings[1].1.contains(Keyboard(Key::Space)));
+}
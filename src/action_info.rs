@@ -0,0 +1,58 @@
+use {Action, Binding};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// Information about an action which an options menu needs to show it. Names and
+/// descriptions are given as keys, which the application looks up in its translations.
+pub trait ActionInfo: Action {
+    /// Returns the key of the name of the action.
+    fn display_name_key(&self) -> &'static str;
+
+    /// Returns the key of a longer description of the action, if it has one.
+    fn description_key(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns the category which the action is grouped under, such as `"movement"`, or
+    /// `None` if it isn't in a category.
+    fn category(&self) -> Option<&'static str> {
+        None
+    }
+
+    /// Returns whether the player may change the buttons bound to the action. Actions
+    /// which can't be rebound, such as opening the menu, are still shown with their
+    /// bindings, but `InputRebind::rebind_action` refuses to change them.
+    ///
+    /// Only `rebind_action` checks this. The other ways of changing bindings, such as
+    /// `InputRebind::insert_action_with_buttons`, `InputRebind::get_bindings_mut` and
+    /// `InputRebind::set_profile`, are for the application itself, and change any action,
+    /// so changes which the player asks for should go through `rebind_action`.
+    fn is_rebindable(&self) -> bool {
+        true
+    }
+}
+
+/// The reason that `InputRebind::rebind_action` refused to change the bindings of an
+/// action.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum RebindError<A: Action> {
+    /// The action can't be rebound.
+    Locked(A),
+
+    /// The button is bound to an action which can't be rebound, so it can't be bound to
+    /// another action.
+    Conflict(A, Binding)
+}
+
+impl<A: Action + Debug> Display for RebindError<A> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            RebindError::Locked(action) => write!(f, "{:?} can't be rebound", action),
+            RebindError::Conflict(action, binding) => {
+                write!(f, "{:?} is bound to {:?}, which can't be rebound", binding, action)
+            }
+        }
+    }
+}
+
+impl<A: Action + Debug> Error for RebindError<A> { }
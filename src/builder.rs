@@ -1,17 +1,25 @@
-use {Action, InputRebind, InputTranslator, MouseTranslationData, to_act_bt_hashmap};
-use input::Button;
+use {Action, Binding, ButtonTranslationData, CalibrationStore, DeviceClass, Diagnostic, GameControllerDb,
+     GamepadMapping, InputRebind, InputTranslator, MouseTranslationData, Profile, ProfileError, Region,
+     RegionBinding, VirtualCursor, to_act_bt_hashmap};
+use callback::Shared;
+use profile;
+use input::MouseButton;
 use window::Size;
 use std::convert::Into;
 use std::collections::hash_map::RandomState;
 use std::hash::BuildHasher;
 use std::default::Default;
 use std::marker::PhantomData;
+use std::sync::Arc;
 
 /// Convenience object for constructing an InputMap.
 #[derive(Debug)]
 pub struct Builder<A: Action, S: BuildHasher = RandomState> {
-    input_remappings: Vec<(Button, A)>,
+    input_remappings: Vec<(Binding, A)>,
+    region_mappings: Vec<RegionBinding<A>>,
     mouse_data: MouseTranslationData,
+    button_data: ButtonTranslationData,
+    virtual_cursor: Option<VirtualCursor>,
     _hasher: PhantomData<S>
 }
 
@@ -20,7 +28,10 @@ impl<A: Action, S: BuildHasher + Default> Builder<A, S> {
     pub fn new<Sz: Into<Size>>(size: Sz) -> Self {
         Builder {
             input_remappings: vec![],
+            region_mappings: vec![],
             mouse_data: MouseTranslationData::new(size),
+            button_data: ButtonTranslationData::new(),
+            virtual_cursor: None,
             _hasher: PhantomData
         }
     }
@@ -75,6 +86,135 @@ impl<A: Action, S: BuildHasher + Default> Builder<A, S> {
         self
     }
 
+    /// Set the scroll distance which makes up one step of a scroll binding. Smaller scroll
+    /// deltas, such as those from a trackpad, are accumulated until a whole step has been
+    /// scrolled. Defaults to `1.0`.
+    pub fn scroll_step(mut self, scroll_step: f64) -> Self {
+        self.mouse_data.scroll_step = scroll_step;
+        self
+    }
+
+    /// Returns the scroll distance which makes up one step of a scroll binding.
+    pub fn get_scroll_step(&self) -> f64 {
+        self.mouse_data.scroll_step
+    }
+
+    /// Set the distance in pixels which the cursor must move while a mouse button bound to
+    /// an action is held for a drag to start. `None` disables drag detection, which is the
+    /// default.
+    pub fn drag_threshold(mut self, drag_threshold: Option<f64>) -> Self {
+        self.mouse_data.drag_threshold = drag_threshold;
+        self
+    }
+
+    /// Returns the distance in pixels which the cursor must move for a drag to start.
+    pub fn get_drag_threshold(&self) -> Option<f64> {
+        self.mouse_data.drag_threshold
+    }
+
+    /// Set the maximum time in seconds between consecutive clicks of a double or triple
+    /// click. Defaults to `0.5`. Clicks are timed by the time passed to
+    /// `InputTranslator::update`, so it must be called every frame for clicks to be told
+    /// apart; without it, every click counts as following the one before.
+    pub fn multi_click_interval(mut self, interval: f64) -> Self {
+        self.mouse_data.multi_click_interval = interval;
+        self
+    }
+
+    /// Returns the maximum time in seconds between consecutive clicks.
+    pub fn get_multi_click_interval(&self) -> f64 {
+        self.mouse_data.multi_click_interval
+    }
+
+    /// Set the maximum distance in pixels which the cursor may move between consecutive
+    /// clicks of a double or triple click. Defaults to `4.0`.
+    pub fn multi_click_distance(mut self, distance: f64) -> Self {
+        self.mouse_data.multi_click_distance = distance;
+        self
+    }
+
+    /// Returns the maximum distance in pixels which the cursor may move between
+    /// consecutive clicks.
+    pub fn get_multi_click_distance(&self) -> f64 {
+        self.mouse_data.multi_click_distance
+    }
+
+    /// Set the mouse button which is held to draw a gesture. `None` disables gesture
+    /// recognition, which is the default.
+    pub fn gesture_button(mut self, button: Option<MouseButton>) -> Self {
+        self.mouse_data.gestures.button = button;
+        self
+    }
+
+    /// Returns the mouse button which is held to draw a gesture.
+    pub fn get_gesture_button(&self) -> Option<MouseButton> {
+        self.mouse_data.gestures.button
+    }
+
+    /// Set the minimum length in pixels of each stroke of a gesture. Shorter movements are
+    /// treated as jitter. Defaults to `50.0`.
+    pub fn gesture_min_stroke_length(mut self, length: f64) -> Self {
+        self.mouse_data.gestures.min_stroke_length = length;
+        self
+    }
+
+    /// Returns the minimum length in pixels of each stroke of a gesture.
+    pub fn get_gesture_min_stroke_length(&self) -> f64 {
+        self.mouse_data.gestures.min_stroke_length
+    }
+
+    /// Set the maximum angle in radians by which a gesture stroke may deviate from a
+    /// `GestureDirection`. Defaults to `PI / 6`.
+    pub fn gesture_direction_tolerance(mut self, tolerance: f64) -> Self {
+        self.mouse_data.gestures.direction_tolerance = tolerance;
+        self
+    }
+
+    /// Returns the maximum angle in radians by which a gesture stroke may deviate from a
+    /// `GestureDirection`.
+    pub fn get_gesture_direction_tolerance(&self) -> f64 {
+        self.mouse_data.gestures.direction_tolerance
+    }
+
+    /// Set the distance in pixels from the edge of the window within which the cursor causes
+    /// edge scrolling, as reported by `InputTranslator::get_edge_scroll`. `None` disables
+    /// edge scrolling, which is the default.
+    pub fn edge_scroll_margin(mut self, margin: Option<f64>) -> Self {
+        self.mouse_data.edge_scroll_margin = margin;
+        self
+    }
+
+    /// Returns the distance in pixels from the edge of the window within which the cursor
+    /// causes edge scrolling.
+    pub fn get_edge_scroll_margin(&self) -> Option<f64> {
+        self.mouse_data.edge_scroll_margin
+    }
+
+    /// Set whether `InputTranslator::translate_stateful` synthesizes `MouseRelative`
+    /// motion from consecutive cursor positions, for backends which only report absolute
+    /// cursor positions.
+    pub fn synthesize_relative_motion(mut self, synthesize: bool) -> Self {
+        self.mouse_data.synthesize_relative_motion = synthesize;
+        self
+    }
+
+    /// Returns true if relative motion is synthesized from cursor positions.
+    pub fn get_synthesize_relative_motion(&self) -> bool {
+        self.mouse_data.synthesize_relative_motion
+    }
+
+    /// Attach a virtual cursor, which can be driven by a controller stick or keys as well
+    /// as the mouse. See `InputTranslator::set_virtual_cursor`.
+    pub fn virtual_cursor(mut self, cursor: Option<VirtualCursor>) -> Self {
+        self.virtual_cursor = cursor;
+        self
+    }
+
+    /// Returns the virtual cursor which will be attached to the translator.
+    pub fn get_virtual_cursor(&self) -> Option<&VirtualCursor> {
+        self.virtual_cursor.as_ref()
+    }
+
     /// Returns the mouse sensitivity.
     pub fn get_mouse_sensitivity(&self) -> f64 {
         self.mouse_data.sensitivity
@@ -91,9 +231,152 @@ impl<A: Action, S: BuildHasher + Default> Builder<A, S> {
         self.mouse_data.viewport_size
     }
 
-    /// Add an association between the Button and Action.
-    pub fn with_mapping(mut self, action: A, button: Button) -> Self {
-        self.input_remappings.push((button, action));
+    /// Set whether the first button press after the window regains focus is ignored by
+    /// `InputTranslator::translate_stateful`. This is useful when the click which
+    /// focuses the window should not trigger an action.
+    pub fn suppress_input_after_focus(mut self, suppress: bool) -> Self {
+        self.button_data.suppress_input_after_focus = suppress;
+        self
+    }
+
+    /// Returns true if the first button press after the window regains focus is ignored.
+    pub fn get_suppress_input_after_focus(&self) -> bool {
+        self.button_data.suppress_input_after_focus
+    }
+
+    /// Set whether presses of a button which is already held (such as those generated by
+    /// the OS key repeat) are ignored by `InputTranslator::translate_stateful`.
+    pub fn filter_repeated_presses(mut self, filter: bool) -> Self {
+        self.button_data.filter_repeated_presses = filter;
+        self
+    }
+
+    /// Returns true if presses of a button which is already held are ignored.
+    pub fn get_filter_repeated_presses(&self) -> bool {
+        self.button_data.filter_repeated_presses
+    }
+
+    /// Set the maximum time in seconds that a button may be held before it is released
+    /// automatically by `InputTranslator::update`. A repeated press of a held button, such as
+    /// from the OS key repeat, restarts its hold time. `None` disables the limit.
+    pub fn max_hold_time(mut self, max_hold_time: Option<f64>) -> Self {
+        self.button_data.max_hold_time = max_hold_time;
+        self
+    }
+
+    /// Returns the maximum time in seconds that a button may be held.
+    pub fn get_max_hold_time(&self) -> Option<f64> {
+        self.button_data.max_hold_time
+    }
+
+    /// Set how far a controller axis must be from the centre for its motion to change the
+    /// last used device class. Defaults to 0.3.
+    pub fn analog_noise_threshold(mut self, threshold: f64) -> Self {
+        self.button_data.analog_noise_threshold = threshold;
+        self
+    }
+
+    /// Returns how far a controller axis must be from the centre to change the last used
+    /// device class.
+    pub fn get_analog_noise_threshold(&self) -> f64 {
+        self.button_data.analog_noise_threshold
+    }
+
+    /// Set the gamepad mapping used for every controller, so that bindings of
+    /// `GamepadButton`s can be translated. Per-controller mappings can be set on the
+    /// `InputTranslator`.
+    pub fn gamepad_mapping(mut self, mapping: Option<GamepadMapping>) -> Self {
+        self.button_data.gamepads.default_mapping = mapping;
+        self
+    }
+
+    /// Returns the gamepad mapping used for every controller.
+    pub fn get_gamepad_mapping(&self) -> Option<&GamepadMapping> {
+        self.button_data.gamepads.default_mapping.as_ref()
+    }
+
+    /// Set the database of gamepad mappings, which are used for controllers as they are
+    /// connected, according to `InputTranslator::controller_event`.
+    pub fn gamepad_database(mut self, database: Option<GameControllerDb>) -> Self {
+        self.button_data.gamepads.database = database.map(Arc::new);
+        self
+    }
+
+    /// Returns the database of gamepad mappings.
+    pub fn get_gamepad_database(&self) -> Option<&GameControllerDb> {
+        self.button_data.gamepads.database.as_ref().map(|db| &**db)
+    }
+
+    /// Set the stored calibrations, which are used for controllers as they are connected,
+    /// according to `InputTranslator::controller_event`.
+    pub fn calibration_store(mut self, store: Option<CalibrationStore>) -> Self {
+        self.button_data.gamepads.calibration_store = store.map(Arc::new);
+        self
+    }
+
+    /// Returns the stored calibrations.
+    pub fn get_calibration_store(&self) -> Option<&CalibrationStore> {
+        self.button_data.gamepads.calibration_store.as_ref().map(|s| &**s)
+    }
+
+    /// Set whether the `South` and `East` gamepad buttons are swapped, so that the confirm
+    /// and cancel buttons follow the Nintendo convention.
+    pub fn swap_confirm_cancel(mut self, swap: bool) -> Self {
+        self.button_data.gamepads.swap_confirm_cancel = swap;
+        self
+    }
+
+    /// Returns true if the `South` and `East` gamepad buttons are swapped.
+    pub fn get_swap_confirm_cancel(&self) -> bool {
+        self.button_data.gamepads.swap_confirm_cancel
+    }
+
+    /// Set the callback which is notified when the translator has to correct for lost or
+    /// unusual input, such as a button which was never released.
+    pub fn diagnostics_callback<F: Fn(&Diagnostic) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.button_data.diagnostics = Some(Shared(Arc::new(callback)));
+        self
+    }
+
+    /// Set the callback which is notified by `InputTranslator::translate_stateful` when the
+    /// last used device class changes.
+    pub fn device_class_callback<F: Fn(DeviceClass) + Send + Sync + 'static>(mut self, callback: F) -> Self {
+        self.button_data.device_class_changed = Some(Shared(Arc::new(callback)));
+        self
+    }
+
+    /// Add an association between the Button and Action. Anything which converts into a
+    /// `Binding`, such as a `ScrollDirection`, can be used in place of a `Button`.
+    pub fn with_mapping<B: Into<Binding>>(mut self, action: A, button: B) -> Self {
+        self.input_remappings.push((button.into(), action));
+        self
+    }
+
+    /// Replace the associations between Buttons and Actions, and the mouse settings, with
+    /// those of the profile. This fails if the profile binds a binding to more than one
+    /// action.
+    pub fn profile(mut self, profile: Profile<A>) -> Result<Self, ProfileError<A>> {
+        profile.check()?;
+        self.input_remappings = profile.keymap
+                                       .iter()
+                                       .flat_map(|&(a, bt)| bt.into_iter().filter_map(|b| b).map(move |b| (b, a)))
+                                       .collect();
+        profile::apply_mouse_settings(&mut self.mouse_data, &profile.mouse);
+        Ok(self)
+    }
+
+    /// Returns the associations between Buttons and Actions, and the mouse settings.
+    pub fn get_profile(&self) -> Profile<A> {
+        let keymap = to_act_bt_hashmap::<_, _, RandomState>(self.input_remappings.iter().cloned());
+        let mut keymap: Vec<_> = keymap.into_iter().collect();
+        keymap.sort_by(|a, b| a.0.cmp(&b.0));
+        Profile { keymap: keymap, mouse: profile::mouse_settings(&self.mouse_data) }
+    }
+
+    /// Add an association between a click of the mouse button inside the region and the
+    /// Action. Regions with a higher `z_order` take precedence where they overlap.
+    pub fn with_region_mapping(mut self, action: A, button: MouseButton, region: Region, z_order: i32) -> Self {
+        self.region_mappings.push(RegionBinding::new(action, button, region, z_order));
         self
     }
 
@@ -120,6 +403,9 @@ impl<A: Action, S: BuildHasher + Default> Into<InputTranslator<A, S>> for Builde
         let mut translator = InputTranslator::new(self.mouse_data.viewport_size);
 
         translator.mouse_translator.data = self.mouse_data;
+        translator.button_data = self.button_data;
+        translator.regions = self.region_mappings;
+        translator.state.virtual_cursor = self.virtual_cursor;
         translator.keymap = self.input_remappings.iter().cloned().collect();
 
         translator
@@ -131,6 +417,9 @@ impl<A: Action, S: BuildHasher + Default> Into<InputRebind<A, S>> for Builder<A,
         let mut rebind = InputRebind::new(self.mouse_data.viewport_size);
 
         rebind.mouse_data = self.mouse_data;
+        rebind.button_data = self.button_data;
+        rebind.regions = self.region_mappings;
+        rebind.state.virtual_cursor = self.virtual_cursor;
         rebind.keymap = to_act_bt_hashmap(self.input_remappings.iter().cloned());
 
         rebind
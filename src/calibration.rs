@@ -0,0 +1,272 @@
+use ControllerGuid;
+use input::ControllerAxisArgs;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// The resting position and the extremes of a controller axis, which are used to correct
+/// an axis that is off centre or doesn't reach the full range.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct AxisCalibration {
+    /// The position of the axis at rest.
+    pub center: f64,
+
+    /// The lowest position which the axis reaches.
+    pub min: f64,
+
+    /// The highest position which the axis reaches.
+    pub max: f64
+}
+
+impl AxisCalibration {
+    /// Correct a raw position, so that the resting position becomes 0, and the extremes
+    /// become -1 and 1.
+    pub fn apply(&self, position: f64) -> f64 {
+        let calibrated = if position >= self.center {
+            (position - self.center) / (self.max - self.center)
+        } else {
+            (position - self.center) / (self.center - self.min)
+        };
+        if calibrated.is_finite() { calibrated.max(-1.0).min(1.0) } else { 0.0 }
+    }
+}
+
+/// The calibration of the axes of one controller.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct DeviceCalibration {
+    axes: Vec<(u8, AxisCalibration)>
+}
+
+impl DeviceCalibration {
+    /// Creates a calibration which leaves every axis unchanged.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Set the calibration of the raw axis index, replacing any previous one.
+    pub fn with_axis(mut self, axis: u8, calibration: AxisCalibration) -> Self {
+        self.axes.retain(|&(a, _)| a != axis);
+        self.axes.push((axis, calibration));
+        self
+    }
+
+    /// Returns the calibration of the raw axis index.
+    pub fn get_axis(&self, axis: u8) -> Option<AxisCalibration> {
+        self.axes.iter().find(|&&(a, _)| a == axis).map(|&(_, c)| c)
+    }
+
+    /// Correct a raw axis motion. Axes without a calibration are unchanged.
+    pub fn apply(&self, args: ControllerAxisArgs) -> ControllerAxisArgs {
+        match self.get_axis(args.axis) {
+            Some(calibration) => ControllerAxisArgs { position: calibration.apply(args.position), ..args },
+            None => args,
+        }
+    }
+}
+
+/// The calibrations of several models of controller, keyed by their GUID, so that they can
+/// be saved and loaded again. They are written one controller to a line, in a format like
+/// that of SDL mapping files, with each axis given as `a<index>:<center>:<min>:<max>`, such
+/// as `030000005e0400008e02000000000000,a0:0.1:-0.9:1,a1:0:-1:1`.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct CalibrationStore {
+    devices: Vec<(ControllerGuid, DeviceCalibration)>
+}
+
+/// A line of stored calibrations which couldn't be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalibrationStoreError {
+    /// The number of the line, starting from 1.
+    pub line: usize,
+
+    /// The GUID or axis which couldn't be read.
+    pub field: String
+}
+
+impl Display for CalibrationStoreError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}: invalid calibration `{}`", self.line, self.field)
+    }
+}
+
+impl Error for CalibrationStoreError { }
+
+impl CalibrationStore {
+    /// Creates an empty store.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// Read calibrations which were written with `to_string`. Blank lines and lines
+    /// starting with `#` are ignored.
+    pub fn parse(text: &str) -> Result<Self, CalibrationStoreError> {
+        let mut store = CalibrationStore::new();
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let invalid = |field: &str| CalibrationStoreError { line: i + 1, field: field.to_string() };
+            let mut fields = line.split(',').map(str::trim).filter(|f| !f.is_empty());
+            let guid_field = fields.next().unwrap_or("");
+            let guid = ControllerGuid::from_hex(guid_field).ok_or_else(|| invalid(guid_field))?;
+            let mut calibration = DeviceCalibration::new();
+            for field in fields {
+                let parts: Vec<_> = field.split(':').collect();
+                let values: Vec<f64> = parts.iter().skip(1).filter_map(|v| v.parse().ok()).collect();
+                let (kind, axis) = parts[0].split_at(if parts[0].is_char_boundary(1) { 1 } else { 0 });
+                match (kind, axis.parse(), &values[..]) {
+                    ("a", Ok(axis), &[center, min, max]) if parts.len() == 4 => {
+                        let axis_calibration = AxisCalibration { center: center, min: min, max: max };
+                        calibration = calibration.with_axis(axis, axis_calibration);
+                    }
+                    _ => return Err(invalid(field)),
+                }
+            }
+            store.insert(guid, calibration);
+        }
+        Ok(store)
+    }
+
+    /// Store the calibration for the model of controller, replacing any previous one.
+    pub fn insert(&mut self, guid: ControllerGuid, calibration: DeviceCalibration) {
+        self.devices.retain(|&(g, _)| g != guid);
+        self.devices.push((guid, calibration));
+    }
+
+    /// Remove the calibration for the model of controller.
+    pub fn remove(&mut self, guid: ControllerGuid) -> Option<DeviceCalibration> {
+        match self.devices.iter().position(|&(g, _)| g == guid) {
+            Some(i) => Some(self.devices.remove(i).1),
+            None => None,
+        }
+    }
+
+    /// Returns the calibration for the model of controller.
+    pub fn get(&self, guid: ControllerGuid) -> Option<&DeviceCalibration> {
+        self.devices.iter().find(|&&(g, _)| g == guid).map(|&(_, ref c)| c)
+    }
+}
+
+impl Display for CalibrationStore {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for &(guid, ref calibration) in &self.devices {
+            write!(f, "{}", guid)?;
+            for &(axis, c) in &calibration.axes {
+                write!(f, ",a{}:{}:{}:{}", axis, c.center, c.min, c.max)?;
+            }
+            writeln!(f, "")?;
+        }
+        Ok(())
+    }
+}
+
+/// A step of a `CalibrationWizard`.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum CalibrationStep {
+    /// The player should leave the sticks at rest, so that their centres can be recorded.
+    Center,
+    /// The player should move the sticks around their full range, so that their extremes
+    /// can be recorded.
+    Range,
+    /// The calibration is finished.
+    Done
+}
+
+/// Calibrates the axes of a controller as the player follows instructions. It doesn't show
+/// anything itself, so the application should show the instruction for the current step,
+/// pass on the controller's axis motion, and call `advance` when the player confirms that
+/// they have finished the step.
+#[derive(Clone, Debug, PartialEq)]
+pub struct CalibrationWizard {
+    controller_id: i32,
+    step: CalibrationStep,
+    min_range: f64,
+    axes: Vec<(u8, AxisCalibration)>
+}
+
+impl CalibrationWizard {
+    /// Start calibrating the controller with the given id.
+    pub fn new(controller_id: i32) -> Self {
+        CalibrationWizard {
+            controller_id: controller_id,
+            step: CalibrationStep::Center,
+            min_range: 0.25,
+            axes: vec![]
+        }
+    }
+
+    /// Set how far an axis must move from its centre in both directions to be calibrated.
+    /// Axes which don't move as far, such as those which weren't touched, are left out of
+    /// the calibration. Defaults to 0.25.
+    pub fn min_range(mut self, min_range: f64) -> Self {
+        self.min_range = min_range;
+        self
+    }
+
+    /// Returns how far an axis must move from its centre in both directions to be
+    /// calibrated.
+    pub fn get_min_range(&self) -> f64 {
+        self.min_range
+    }
+
+    /// Returns the id of the controller which is being calibrated.
+    pub fn get_controller_id(&self) -> i32 {
+        self.controller_id
+    }
+
+    /// Returns the current step.
+    pub fn get_step(&self) -> CalibrationStep {
+        self.step
+    }
+
+    /// Record a raw axis motion. Motion of other controllers is ignored.
+    pub fn axis_moved(&mut self, args: ControllerAxisArgs) {
+        if args.id != self.controller_id {
+            return;
+        }
+        let pos = args.position;
+        let step = self.step;
+        match self.axes.iter().position(|&(a, _)| a == args.axis) {
+            Some(i) => {
+                let c = &mut self.axes[i].1;
+                match step {
+                    CalibrationStep::Center => *c = AxisCalibration { center: pos, min: pos, max: pos },
+                    CalibrationStep::Range => {
+                        c.min = c.min.min(pos);
+                        c.max = c.max.max(pos);
+                    }
+                    CalibrationStep::Done => {}
+                }
+            }
+            None => {
+                // An axis which didn't report a resting position is assumed to rest at 0
+                let center = if step == CalibrationStep::Center { pos } else { 0.0 };
+                if step != CalibrationStep::Done {
+                    let c = AxisCalibration { center: center, min: pos.min(center), max: pos.max(center) };
+                    self.axes.push((args.axis, c));
+                }
+            }
+        }
+    }
+
+    /// Move on to the next step.
+    pub fn advance(&mut self) -> CalibrationStep {
+        self.step = match self.step {
+            CalibrationStep::Center => CalibrationStep::Range,
+            _ => CalibrationStep::Done,
+        };
+        self.step
+    }
+
+    /// Returns the calibration once the wizard is done.
+    pub fn get_calibration(&self) -> Option<DeviceCalibration> {
+        if self.step != CalibrationStep::Done {
+            return None;
+        }
+        let min_range = self.min_range;
+        let calibrated = self.axes.iter().filter(|&&(_, c)| {
+            c.center - c.min >= min_range && c.max - c.center >= min_range
+        });
+        Some(calibrated.fold(DeviceCalibration::new(), |d, &(a, c)| d.with_axis(a, c)))
+    }
+}
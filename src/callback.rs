@@ -0,0 +1,26 @@
+use std::fmt::{Debug, Formatter, Result};
+use std::sync::Arc;
+
+/// A shared handle to something supplied by the application, such as a callback or a
+/// string table, so that it can be cloned along with the `Builder`, `InputRebind`,
+/// `InputTranslator` or other value which holds it, without stopping that value from
+/// being sent between threads. Two handles are equal if they share the same object.
+pub struct Shared<T: ?Sized>(pub Arc<T>);
+
+impl<T: ?Sized> Clone for Shared<T> {
+    fn clone(&self) -> Self {
+        Shared(self.0.clone())
+    }
+}
+
+impl<T: ?Sized> Debug for Shared<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "Shared")
+    }
+}
+
+impl<T: ?Sized> PartialEq for Shared<T> {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
@@ -0,0 +1,131 @@
+use {Binding, ParseBindingError};
+use input::{Button, Key};
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// The modifier keys which are held for a `Chord`. Each modifier stands for both its left
+/// and right key.
+#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
+pub struct Modifiers {
+    /// Whether a control key is held.
+    pub ctrl: bool,
+
+    /// Whether a shift key is held.
+    pub shift: bool,
+
+    /// Whether an alt key is held.
+    pub alt: bool,
+
+    /// Whether a GUI key, such as the Windows or Command key, is held.
+    pub gui: bool
+}
+
+/// A binding which is pressed while modifier keys are held, such as `Ctrl+Shift+A`.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct Chord {
+    /// The modifier keys which are held.
+    pub modifiers: Modifiers,
+
+    /// The binding which is pressed.
+    pub binding: Binding
+}
+
+impl From<Binding> for Chord {
+    fn from(binding: Binding) -> Self {
+        Chord { modifiers: Default::default(), binding: binding }
+    }
+}
+
+/// Returns the modifiers which are held while the keys are held.
+pub fn modifiers_held(keys: &[Key]) -> Modifiers {
+    let held = |left, right| keys.iter().any(|&k| k == left || k == right);
+    Modifiers {
+        ctrl: held(Key::LCtrl, Key::RCtrl),
+        shift: held(Key::LShift, Key::RShift),
+        alt: held(Key::LAlt, Key::RAlt),
+        gui: held(Key::LGui, Key::RGui)
+    }
+}
+
+/// Returns true if the key is one of the modifier keys of a `Chord`.
+pub fn is_modifier(key: Key) -> bool {
+    modifiers_held(&[key]) != Modifiers::default()
+}
+
+/// Chords which are pressed one after another, such as `Ctrl+K Ctrl+C`.
+#[derive(Clone, Debug, Eq, Hash, PartialEq)]
+pub struct Sequence(pub Vec<Chord>);
+
+/// Chords are written as their modifiers and binding separated by `+`. The modifiers are
+/// written as `Ctrl`, `Shift`, `Alt` and `Gui`, in that order.
+impl Display for Chord {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        let modifiers = [(self.modifiers.ctrl, "Ctrl"),
+                         (self.modifiers.shift, "Shift"),
+                         (self.modifiers.alt, "Alt"),
+                         (self.modifiers.gui, "Gui")];
+        for &(_, name) in modifiers.iter().filter(|m| m.0) {
+            write!(f, "{}+", name)?;
+        }
+        write!(f, "{}", self.binding)
+    }
+}
+
+/// Parses a chord written as it is displayed. The modifiers may be given in any order,
+/// and may be spelled `Control`, `Super`, `Cmd`, `Win` or `Meta`. A chord with modifiers
+/// can't end with a modifier key, as in `Ctrl+Shift`, because that key would be displayed
+/// as `LShift`, which is a different chord.
+impl FromStr for Chord {
+    type Err = ParseBindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let error = || ParseBindingError { text: s.to_string() };
+        let mut parts = s.trim().split('+').map(str::trim).collect::<Vec<_>>();
+        let binding = parts.pop().ok_or_else(error)?.parse().map_err(|_| error())?;
+
+        let mut modifiers = Modifiers::default();
+        for part in parts {
+            let modifier = match &*part.to_ascii_lowercase() {
+                "ctrl" | "control" => &mut modifiers.ctrl,
+                "shift" => &mut modifiers.shift,
+                "alt" => &mut modifiers.alt,
+                "gui" | "super" | "cmd" | "win" | "meta" => &mut modifiers.gui,
+                _ => return Err(error()),
+            };
+            *modifier = true;
+        }
+        match binding {
+            Binding::Button(Button::Keyboard(key)) if is_modifier(key) && modifiers != Modifiers::default() => {
+                Err(error())
+            }
+            _ => Ok(Chord { modifiers: modifiers, binding: binding }),
+        }
+    }
+}
+
+/// Sequences are written as their chords separated by spaces.
+impl Display for Sequence {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for (i, chord) in self.0.iter().enumerate() {
+            if i > 0 {
+                write!(f, " ")?;
+            }
+            write!(f, "{}", chord)?;
+        }
+        Ok(())
+    }
+}
+
+/// Parses a sequence written as it is displayed. A sequence has at least one chord.
+impl FromStr for Sequence {
+    type Err = ParseBindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let chords = s.split_whitespace().map(str::parse).collect::<Result<Vec<Chord>, _>>();
+        match chords {
+            Ok(ref chords) if chords.is_empty() => Err(ParseBindingError { text: s.to_string() }),
+            Ok(chords) => Ok(Sequence(chords)),
+            Err(_) => Err(ParseBindingError { text: s.to_string() }),
+        }
+    }
+}
@@ -0,0 +1,67 @@
+use input::MouseButton;
+
+/// Counts consecutive clicks of each mouse button, so that double and triple clicks can be
+/// recognised. Clicks are consecutive if they are pressed within the maximum interval of
+/// each other, and the cursor has not moved further than the maximum distance between
+/// them.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ClickCounter {
+    clicks: Vec<Click>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Click {
+    button: MouseButton,
+    time: f64,
+    pos: Option<[f64; 2]>,
+    count: u32
+}
+
+impl ClickCounter {
+    pub fn new() -> Self {
+        ClickCounter { clicks: vec![] }
+    }
+
+    /// Record a press of the mouse button, returning how many consecutive clicks it makes
+    /// up. After a triple click, counting starts again from one.
+    pub fn press(&mut self,
+                 button: MouseButton,
+                 time: f64,
+                 pos: Option<[f64; 2]>,
+                 max_interval: f64,
+                 max_distance: f64)
+                 -> u32 {
+        match self.clicks.iter().position(|c| c.button == button) {
+            Some(i) => {
+                let click = &mut self.clicks[i];
+                let travel = match (click.pos, pos) {
+                    (Some(p0), Some(p1)) => ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt(),
+                    _ => 0.0,
+                };
+
+                if time - click.time <= max_interval && travel <= max_distance && click.count < 3 {
+                    click.count += 1;
+                } else {
+                    click.count = 1;
+                }
+                click.time = time;
+                click.pos = pos;
+                click.count
+            }
+            None => {
+                self.clicks.push(Click {
+                    button: button,
+                    time: time,
+                    pos: pos,
+                    count: 1
+                });
+                1
+            }
+        }
+    }
+
+    /// Forget all previous clicks.
+    pub fn reset(&mut self) {
+        self.clicks.clear();
+    }
+}
@@ -0,0 +1,442 @@
+use {Binding, ButtonTuple, NamedAction, Profile, ProfileError};
+use notation;
+use profile::MouseSettings;
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// A keymap and mouse settings in a text format which is meant to be edited by hand. Each
+/// line of the keymap binds an action, named as it is by `NamedAction`, to up to three
+/// bindings, written as they are displayed. The mouse settings follow a `[mouse]` header,
+/// and anything after a `#` is a comment:
+///
+/// ```text
+/// # Movement
+/// jump = Space, Gamepad.South
+/// fire = Mouse.Left
+///
+/// [mouse]
+/// invert_y_motion = true
+/// sensitivity = 0.5
+/// ```
+///
+/// The mouse settings are named as the fields of `MouseSettings` are, and as they are in a
+/// serialized `Profile`: `invert_x_motion`, `invert_y_motion`, `invert_x_scroll`,
+/// `invert_y_scroll`, `sensitivity`, `scroll_step`, `drag_threshold`,
+/// `multi_click_interval`, `multi_click_distance` and `edge_scroll_margin`.
+/// `drag_threshold` and `edge_scroll_margin` can be `none`.
+///
+/// The profile which is read can be given to `Builder::profile` or
+/// `InputRebind::set_profile`. When a profile is set, the text is rewritten in place, so
+/// that comments and the order of the lines are kept.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingConfig<A: NamedAction> {
+    lines: Vec<String>,
+    profile: Profile<A>
+}
+
+/// A line of a binding config which couldn't be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct BindingConfigError {
+    /// The number of the line, starting from 1.
+    pub line: usize,
+
+    /// The column where the error starts, counted in characters from 1.
+    pub column: usize,
+
+    /// What is wrong with the line.
+    pub kind: BindingConfigErrorKind,
+
+    /// The closest valid name, if the name looks misspelled.
+    pub suggestion: Option<String>
+}
+
+/// What is wrong with a line of a binding config.
+#[derive(Clone, Debug, PartialEq)]
+pub enum BindingConfigErrorKind {
+    /// The line isn't a section header, and doesn't have an `=`.
+    MissingEquals,
+
+    /// There isn't an action with the name.
+    UnknownAction(String),
+
+    /// The binding couldn't be parsed.
+    UnknownBinding(String),
+
+    /// An action has more than three bindings.
+    TooManyBindings,
+
+    /// There isn't a setting with the name.
+    UnknownSetting(String),
+
+    /// There isn't a section with the name.
+    UnknownSection(String),
+
+    /// The value isn't valid for the setting.
+    InvalidValue(String),
+
+    /// The action or setting has already been given.
+    DuplicateEntry(String),
+
+    /// The binding is already bound to the action with the name, which may be the same
+    /// action. A binding can only be bound to one action.
+    DuplicateBinding(String, String)
+}
+
+impl Display for BindingConfigError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "line {}, column {}: ", self.line, self.column)?;
+        match self.kind {
+            BindingConfigErrorKind::MissingEquals => write!(f, "expected `name = value`")?,
+            BindingConfigErrorKind::UnknownAction(ref name) => write!(f, "unknown action `{}`", name)?,
+            BindingConfigErrorKind::UnknownBinding(ref name) => write!(f, "unknown binding `{}`", name)?,
+            BindingConfigErrorKind::TooManyBindings => write!(f, "an action can't have more than 3 bindings")?,
+            BindingConfigErrorKind::UnknownSetting(ref name) => write!(f, "unknown setting `{}`", name)?,
+            BindingConfigErrorKind::UnknownSection(ref name) => write!(f, "unknown section `{}`", name)?,
+            BindingConfigErrorKind::InvalidValue(ref value) => write!(f, "invalid value `{}`", value)?,
+            BindingConfigErrorKind::DuplicateEntry(ref name) => write!(f, "`{}` is given more than once", name)?,
+            BindingConfigErrorKind::DuplicateBinding(ref binding, ref action) => {
+                write!(f, "`{}` is already bound to `{}`", binding, action)?
+            }
+        }
+        match self.suggestion {
+            Some(ref suggestion) => write!(f, " (did you mean `{}`?)", suggestion),
+            None => Ok(()),
+        }
+    }
+}
+
+impl Error for BindingConfigError { }
+
+const MOUSE_SECTION: &'static str = "mouse";
+
+/// The names of the settings, which are those of the fields of `MouseSettings`, so that a
+/// setting can be copied between a config and a serialized `Profile`.
+const MOUSE_SETTINGS: &'static [&'static str] = &["invert_x_motion", "invert_y_motion", "invert_x_scroll",
+                                                  "invert_y_scroll", "sensitivity", "scroll_step", "drag_threshold",
+                                                  "multi_click_interval", "multi_click_distance",
+                                                  "edge_scroll_margin"];
+
+/// A line split into its parts. Offsets are in bytes from the start of the line.
+enum Line<'a> {
+    Blank,
+    Section(&'a str, usize),
+    Entry {
+        key: &'a str,
+        key_offset: usize,
+        value: &'a str,
+        value_offset: usize,
+        indent: &'a str,
+        trailing: &'a str
+    },
+    Invalid(usize)
+}
+
+fn split_line<'a>(line: &'a str) -> Line<'a> {
+    let code_len = line.find('#').unwrap_or(line.len());
+    let code = line[..code_len].trim_end();
+    let trailing = &line[code.len()..];
+    let start = code.len() - code.trim_start().len();
+    let indent = &code[..start];
+    let code = &code[start..];
+    if code.is_empty() {
+        return Line::Blank;
+    }
+    if code.starts_with('[') && code.ends_with(']') && code.len() >= 2 {
+        let name = &code[1..code.len() - 1];
+        let name_offset = start + 1 + name.len() - name.trim_start().len();
+        return Line::Section(name.trim(), name_offset);
+    }
+    match code.find('=') {
+        Some(equals) => {
+            let key = code[..equals].trim_end();
+            let value = &code[equals + 1..];
+            let value_offset = start + equals + 1 + value.len() - value.trim_start().len();
+            Line::Entry {
+                key: key,
+                key_offset: start,
+                value: value.trim(),
+                value_offset: value_offset,
+                indent: indent,
+                trailing: trailing
+            }
+        }
+        None => Line::Invalid(start),
+    }
+}
+
+/// Returns the number of single character edits, or swaps of adjacent characters, which
+/// turn one name into the other, ignoring case.
+fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().flat_map(char::to_lowercase).collect();
+    let b: Vec<char> = b.chars().flat_map(char::to_lowercase).collect();
+    let mut d = vec![vec![0; b.len() + 1]; a.len() + 1];
+    for i in 0..a.len() + 1 {
+        d[i][0] = i;
+    }
+    for j in 0..b.len() + 1 {
+        d[0][j] = j;
+    }
+    for i in 1..a.len() + 1 {
+        for j in 1..b.len() + 1 {
+            let cost = if a[i - 1] == b[j - 1] { 0 } else { 1 };
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Returns the candidate which is closest to the name, if it is close enough to be a
+/// misspelling.
+fn suggest<I: IntoIterator<Item = String>>(name: &str, candidates: I) -> Option<String> {
+    let max_distance = ::std::cmp::max(1, name.chars().count() / 3);
+    candidates.into_iter()
+              .map(|c| (edit_distance(name, &c), c))
+              .filter(|&(d, _)| d <= max_distance)
+              .min_by_key(|&(d, _)| d)
+              .map(|(_, c)| c)
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+    match value {
+        "true" => Some(true),
+        "false" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_optional(value: &str) -> Option<Option<f64>> {
+    if value == "none" { Some(None) } else { value.parse().ok().map(Some) }
+}
+
+fn apply_setting(settings: &mut MouseSettings, name: &str, value: &str) -> Option<()> {
+    match name {
+        "invert_x_motion" => settings.invert_x_motion = parse_flag(value)?,
+        "invert_y_motion" => settings.invert_y_motion = parse_flag(value)?,
+        "invert_x_scroll" => settings.invert_x_scroll = parse_flag(value)?,
+        "invert_y_scroll" => settings.invert_y_scroll = parse_flag(value)?,
+        "sensitivity" => settings.sensitivity = value.parse().ok()?,
+        "scroll_step" => settings.scroll_step = value.parse().ok()?,
+        "drag_threshold" => settings.drag_threshold = parse_optional(value)?,
+        "multi_click_interval" => settings.multi_click_interval = value.parse().ok()?,
+        "multi_click_distance" => settings.multi_click_distance = value.parse().ok()?,
+        "edge_scroll_margin" => settings.edge_scroll_margin = parse_optional(value)?,
+        _ => return None,
+    }
+    Some(())
+}
+
+fn setting_value(settings: &MouseSettings, name: &str) -> String {
+    let optional = |value: Option<f64>| value.map_or("none".to_string(), |v| v.to_string());
+    match name {
+        "invert_x_motion" => settings.invert_x_motion.to_string(),
+        "invert_y_motion" => settings.invert_y_motion.to_string(),
+        "invert_x_scroll" => settings.invert_x_scroll.to_string(),
+        "invert_y_scroll" => settings.invert_y_scroll.to_string(),
+        "sensitivity" => settings.sensitivity.to_string(),
+        "scroll_step" => settings.scroll_step.to_string(),
+        "drag_threshold" => optional(settings.drag_threshold),
+        "multi_click_interval" => settings.multi_click_interval.to_string(),
+        "multi_click_distance" => settings.multi_click_distance.to_string(),
+        _ => optional(settings.edge_scroll_margin),
+    }
+}
+
+fn bindings_value(buttons: &ButtonTuple) -> String {
+    buttons.into_iter().filter_map(|b| b).map(|b| b.to_string()).collect::<Vec<_>>().join(", ")
+}
+
+impl<A: NamedAction> BindingConfig<A> {
+    /// Creates an empty config, with no bindings and the default mouse settings.
+    pub fn new() -> Self {
+        BindingConfig { lines: vec![], profile: Profile::default() }
+    }
+
+    /// Read a config. The first line which can't be read is returned as an error.
+    pub fn parse(text: &str) -> Result<Self, BindingConfigError> {
+        let mut profile = Profile::default();
+        let mut in_mouse_section = false;
+        let mut seen: Vec<(bool, &str)> = vec![];
+        let mut bound: Vec<(Binding, A)> = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let error = |offset: usize, kind, suggestion| {
+                BindingConfigError {
+                    line: i + 1,
+                    column: line[..offset].chars().count() + 1,
+                    kind: kind,
+                    suggestion: suggestion
+                }
+            };
+            match split_line(line) {
+                Line::Blank => {}
+                Line::Section(name, offset) => {
+                    if name != MOUSE_SECTION {
+                        let suggestion = suggest(name, Some(MOUSE_SECTION.to_string()));
+                        return Err(error(offset, BindingConfigErrorKind::UnknownSection(name.to_string()), suggestion));
+                    }
+                    in_mouse_section = true;
+                }
+                Line::Invalid(offset) => return Err(error(offset, BindingConfigErrorKind::MissingEquals, None)),
+                Line::Entry { key, key_offset, .. } if seen.contains(&(in_mouse_section, key)) => {
+                    let kind = BindingConfigErrorKind::DuplicateEntry(key.to_string());
+                    return Err(error(key_offset, kind, None));
+                }
+                Line::Entry { key, key_offset, value, value_offset, .. } if in_mouse_section => {
+                    if !MOUSE_SETTINGS.contains(&key) {
+                        let suggestion = suggest(key, MOUSE_SETTINGS.iter().map(|s| s.to_string()));
+                        let kind = BindingConfigErrorKind::UnknownSetting(key.to_string());
+                        return Err(error(key_offset, kind, suggestion));
+                    }
+                    if apply_setting(&mut profile.mouse, key, value).is_none() {
+                        let kind = BindingConfigErrorKind::InvalidValue(value.to_string());
+                        return Err(error(value_offset, kind, None));
+                    }
+                    seen.push((true, key));
+                }
+                Line::Entry { key, key_offset, value, value_offset, .. } => {
+                    let action = match A::from_name(key) {
+                        Some(action) => action,
+                        None => {
+                            let suggestion = suggest(key, A::all().iter().map(|a| a.name().to_string()));
+                            let kind = BindingConfigErrorKind::UnknownAction(key.to_string());
+                            return Err(error(key_offset, kind, suggestion));
+                        }
+                    };
+                    let mut buttons = ButtonTuple::new();
+                    let mut offset = value_offset;
+                    for name in value.split(',') {
+                        let trimmed = name.trim();
+                        let start = offset + name.len() - name.trim_start().len();
+                        offset += name.len() + 1;
+                        if trimmed.is_empty() && value.is_empty() {
+                            continue;
+                        }
+                        let binding = match trimmed.parse::<Binding>() {
+                            Ok(binding) => binding,
+                            Err(_) => {
+                                let suggestion = suggest(trimmed, notation::binding_names());
+                                let kind = BindingConfigErrorKind::UnknownBinding(trimmed.to_string());
+                                return Err(error(start, kind, suggestion));
+                            }
+                        };
+                        if let Some(&(_, other)) = bound.iter().find(|&&(b, _)| b == binding) {
+                            let kind = BindingConfigErrorKind::DuplicateBinding(trimmed.to_string(),
+                                                                                other.name().to_string());
+                            return Err(error(start, kind, None));
+                        }
+                        if !buttons.insert_inplace(binding) {
+                            return Err(error(start, BindingConfigErrorKind::TooManyBindings, None));
+                        }
+                        bound.push((binding, action));
+                    }
+                    profile.keymap.push((action, buttons));
+                    seen.push((false, key));
+                }
+            }
+        }
+        profile.keymap.sort_by(|a, b| a.0.cmp(&b.0));
+        Ok(BindingConfig { lines: text.lines().map(String::from).collect(), profile: profile })
+    }
+
+    /// Returns the keymap and mouse settings which were read.
+    pub fn get_profile(&self) -> &Profile<A> {
+        &self.profile
+    }
+
+    /// Replace the keymap and mouse settings. The lines of actions and settings which are
+    /// already in the text are rewritten in place, keeping their comments. Actions which
+    /// aren't in the text are added after the last action, and mouse settings which aren't
+    /// in the text are added to the `[mouse]` section if they differ from the default.
+    /// Nothing is changed if the profile binds a binding to more than one action, since
+    /// the text couldn't be read again.
+    pub fn set_profile(&mut self, profile: Profile<A>) -> Result<(), ProfileError<A>> {
+        profile.check()?;
+        let mut written = vec![];
+        let mut in_mouse_section = false;
+        let mut last_action_line = None;
+        let mut first_section_line = None;
+        let mut mouse_section_end = None;
+        for i in 0..self.lines.len() {
+            let rewritten = match split_line(&self.lines[i]) {
+                Line::Section(..) => {
+                    in_mouse_section = true;
+                    first_section_line = first_section_line.or(Some(i));
+                    mouse_section_end = Some(i + 1);
+                    None
+                }
+                Line::Entry { key, indent, trailing, .. } => {
+                    written.push((in_mouse_section, key.to_string()));
+                    let value = if in_mouse_section {
+                        mouse_section_end = Some(i + 1);
+                        setting_value(&profile.mouse, key)
+                    } else {
+                        last_action_line = Some(i + 1);
+                        let buttons = profile.keymap.iter().find(|&&(a, _)| a.name() == key).map(|&(_, bt)| bt);
+                        bindings_value(&buttons.unwrap_or_default())
+                    };
+                    Some(format!("{}{} = {}{}", indent, key, value, trailing).trim_end().to_string())
+                }
+                Line::Blank | Line::Invalid(_) => None,
+            };
+            if let Some(line) = rewritten {
+                self.lines[i] = line;
+            }
+        }
+        let is_written = |mouse: bool, key: &str| written.iter().any(|&(m, ref k)| m == mouse && k == key);
+
+        let mut actions: Vec<String> = profile.keymap
+                                              .iter()
+                                              .filter(|&&(a, _)| !is_written(false, a.name()))
+                                              .map(|&(a, ref bt)| format!("{} = {}", a.name(), bindings_value(bt)))
+                                              .collect();
+        if last_action_line.is_none() && first_section_line.is_some() && !actions.is_empty() {
+            actions.push(String::new());
+        }
+        let at = last_action_line.or(first_section_line).unwrap_or(self.lines.len());
+        let added_actions = actions.len();
+        for (j, line) in actions.into_iter().enumerate() {
+            self.lines.insert(at + j, line);
+        }
+
+        let defaults = MouseSettings::default();
+        let changed = |s: &str| setting_value(&profile.mouse, s) != setting_value(&defaults, s);
+        let settings: Vec<String> = MOUSE_SETTINGS.iter()
+                                                  .filter(|&&s| !is_written(true, s) && changed(s))
+                                                  .map(|&s| format!("{} = {}", s, setting_value(&profile.mouse, s)))
+                                                  .collect();
+        match mouse_section_end {
+            Some(end) => {
+                for (j, line) in settings.into_iter().enumerate() {
+                    self.lines.insert(end + added_actions + j, line);
+                }
+            }
+            None if !settings.is_empty() => {
+                if !self.lines.is_empty() {
+                    self.lines.push(String::new());
+                }
+                self.lines.push(format!("[{}]", MOUSE_SECTION));
+                self.lines.extend(settings);
+            }
+            None => {}
+        }
+        self.profile = profile;
+        Ok(())
+    }
+}
+
+impl<A: NamedAction> Default for BindingConfig<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<A: NamedAction> Display for BindingConfig<A> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        for line in &self.lines {
+            writeln!(f, "{}", line)?;
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,406 @@
+use {ActionInfo, Binding, ButtonTuple, InputRebind, NamedAction, Translated};
+use callback::Shared;
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+use std::fs::File;
+use std::hash::BuildHasher;
+use std::io::Read;
+use std::sync::Arc;
+
+/// Interprets the binding commands of a Quake style developer console, changing the
+/// bindings of an `InputRebind`:
+///
+/// * `bind <button> +action` binds the button to an action in the `InputRebind`, so that
+///   the action is held for as long as the button is.
+/// * `bind <button> <command>` binds the button to a one-shot command, such as `pause` or
+///   `"exec binds.cfg"`, which is kept by the console and run by `run_bound_command` when
+///   the button is pressed.
+/// * `bind <button>` shows what the button is bound to.
+/// * `unbind <button>` removes the button from its action or command.
+/// * `unbindall` removes every binding.
+/// * `bindlist` lists every binding.
+/// * `exec <script>` runs the commands in a script.
+/// * `+action` and `-action` press and release an action, as if a button bound to it had
+///   been pressed or released.
+/// * `action` taps an action, pressing and releasing it at once.
+///
+/// Buttons are written as they are displayed by `Binding`, ignoring case, such as `w`,
+/// `space` or `mouse.left`. Arguments can be quoted, several commands can be given on a
+/// line separated by `;`, and anything after `//` is a comment.
+///
+/// Every action can be rebound, unless the console is made to honour
+/// `ActionInfo::is_rebindable` with `lock_actions`.
+#[derive(Debug)]
+pub struct BindConsole<A: NamedAction> {
+    held: Vec<A>,
+    commands: Vec<(Binding, String)>,
+    rebindable: fn(A) -> bool,
+    loader: ScriptLoader,
+    executing: Vec<String>
+}
+
+/// What a console command produced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ConsoleOutput<A: NamedAction> {
+    /// The actions which were pressed or released with `+action` and `-action`.
+    pub actions: Vec<Translated<A>>,
+
+    /// The lines of text which should be shown in the console.
+    pub lines: Vec<String>
+}
+
+/// A console command which couldn't be run.
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConsoleError {
+    /// There isn't a command with the name.
+    UnknownCommand(String),
+
+    /// The command was given the wrong arguments. This is the usage of the command.
+    Usage(&'static str),
+
+    /// The button couldn't be parsed.
+    UnknownBinding(String),
+
+    /// There isn't an action with the name.
+    UnknownAction(String),
+
+    /// The action already has three buttons bound to it.
+    TooManyBindings(String),
+
+    /// The action can't be rebound.
+    Locked(String),
+
+    /// The button is bound to the action, which can't be rebound, so it can't be bound to
+    /// anything else.
+    Conflict(String, String),
+
+    /// The script couldn't be loaded.
+    ScriptNotFound(String),
+
+    /// The script executes itself, directly or through other scripts.
+    RecursiveExec(String),
+
+    /// A command in a script couldn't be run.
+    Script {
+        /// The name of the script.
+        name: String,
+
+        /// The number of the line, starting from 1.
+        line: usize,
+
+        /// The error of the command.
+        error: Box<ConsoleError>
+    }
+}
+
+impl Display for ConsoleError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ConsoleError::UnknownCommand(ref name) => write!(f, "unknown command `{}`", name),
+            ConsoleError::Usage(usage) => write!(f, "usage: {}", usage),
+            ConsoleError::UnknownBinding(ref name) => write!(f, "unknown button `{}`", name),
+            ConsoleError::UnknownAction(ref name) => write!(f, "unknown action `{}`", name),
+            ConsoleError::TooManyBindings(ref name) => write!(f, "`{}` already has 3 buttons bound to it", name),
+            ConsoleError::Locked(ref name) => write!(f, "`{}` can't be rebound", name),
+            ConsoleError::Conflict(ref button, ref name) => {
+                write!(f, "`{}` is bound to `{}`, which can't be rebound", button, name)
+            }
+            ConsoleError::ScriptNotFound(ref name) => write!(f, "couldn't exec `{}`", name),
+            ConsoleError::RecursiveExec(ref name) => write!(f, "`{}` executes itself", name),
+            ConsoleError::Script { ref name, line, ref error } => write!(f, "{}, line {}: {}", name, line, error),
+        }
+    }
+}
+
+impl Error for ConsoleError { }
+
+/// Loads the text of the scripts run with `exec`.
+type ScriptLoader = Shared<dyn Fn(&str) -> Option<String> + Send + Sync>;
+
+fn read_file(path: &str) -> Option<String> {
+    let mut text = String::new();
+    match File::open(path).and_then(|mut file| file.read_to_string(&mut text)) {
+        Ok(_) => Some(text),
+        Err(_) => None,
+    }
+}
+
+/// Splits a line into commands, and each command into its arguments.
+fn tokenize(line: &str) -> Vec<Vec<String>> {
+    let mut commands = vec![];
+    let mut args = vec![];
+    let mut arg = None::<String>;
+    let mut quoted = false;
+    let mut chars = line.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                arg = arg.or_else(|| Some(String::new()));
+            }
+            '/' if !quoted && chars.peek() == Some(&'/') => break,
+            ';' if !quoted => {
+                args.extend(arg.take());
+                commands.push(args);
+                args = vec![];
+            }
+            c if c.is_whitespace() && !quoted => args.extend(arg.take()),
+            c => arg.get_or_insert_with(String::new).push(c),
+        }
+    }
+    args.extend(arg);
+    commands.push(args);
+    commands.into_iter().filter(|c| !c.is_empty()).collect()
+}
+
+/// Quotes an argument if it contains characters which would split it.
+fn quote(arg: &str) -> String {
+    if arg.is_empty() || arg.contains(|c: char| c.is_whitespace() || c == ';' || c == '"') {
+        format!("\"{}\"", arg)
+    } else {
+        arg.to_string()
+    }
+}
+
+/// Quotes an argument, as `bind` and `bindlist` show commands.
+fn quote_always(arg: &str) -> String {
+    format!("\"{}\"", arg)
+}
+
+fn parse_binding(name: &str) -> Result<Binding, ConsoleError> {
+    name.parse().map_err(|_| ConsoleError::UnknownBinding(name.to_string()))
+}
+
+fn parse_action<A: NamedAction>(name: &str) -> Result<A, ConsoleError> {
+    A::from_name(name).ok_or_else(|| ConsoleError::UnknownAction(name.to_string()))
+}
+
+impl<A: NamedAction> BindConsole<A> {
+    /// Creates a console, which loads scripts from files, with paths relative to the
+    /// current directory.
+    pub fn new() -> Self {
+        BindConsole {
+            held: vec![],
+            commands: vec![],
+            rebindable: |_| true,
+            loader: Shared(Arc::new(read_file)),
+            executing: vec![]
+        }
+    }
+
+    /// Set how the scripts run with `exec` are loaded, for example from the application's
+    /// assets. The loader is given the name of the script, and returns its text, or `None`
+    /// if it can't be loaded.
+    pub fn script_loader<F: Fn(&str) -> Option<String> + Send + Sync + 'static>(mut self, loader: F) -> Self {
+        self.loader = Shared(Arc::new(loader));
+        self
+    }
+
+    /// Returns the actions which are held with `+action`.
+    pub fn get_held_actions(&self) -> &[A] {
+        &self.held
+    }
+
+    /// Returns the one-shot command which the button is bound to.
+    pub fn get_bound_command(&self, binding: Binding) -> Option<&str> {
+        self.commands.iter().find(|&&(b, _)| b == binding).map(|&(_, ref c)| &c[..])
+    }
+
+    /// Run the one-shot command which the button is bound to, if it is bound to one. This
+    /// should be called when the button is pressed.
+    pub fn run_bound_command<S>(&mut self, rebind: &mut InputRebind<A, S>, binding: Binding)
+                                -> Result<ConsoleOutput<A>, ConsoleError>
+        where S: BuildHasher + Default
+    {
+        match self.get_bound_command(binding).map(String::from) {
+            Some(command) => self.execute(rebind, &command),
+            None => Ok(ConsoleOutput { actions: vec![], lines: vec![] }),
+        }
+    }
+
+    /// Run a line of commands. If one of them fails, its error is returned, and the line
+    /// has no effect: the bindings and held actions are left as they were before it.
+    pub fn execute<S>(&mut self, rebind: &mut InputRebind<A, S>, line: &str) -> Result<ConsoleOutput<A>, ConsoleError>
+        where S: BuildHasher + Default
+    {
+        let held = self.held.clone();
+        let commands = self.commands.clone();
+        let keymap: Vec<_> = rebind.keymap.iter().map(|(&a, &bt)| (a, bt)).collect();
+        let mut output = ConsoleOutput { actions: vec![], lines: vec![] };
+        let result = self.run_line(rebind, line, &mut output);
+        if let Err(error) = result {
+            self.held = held;
+            self.commands = commands;
+            rebind.keymap = keymap.into_iter().collect();
+            return Err(error);
+        }
+        Ok(output)
+    }
+
+    /// Returns a script which restores the current bindings when it is run with `exec`.
+    pub fn bind_script<S: BuildHasher + Default>(&self, rebind: &InputRebind<A, S>) -> String {
+        let mut script = "unbindall\n".to_string();
+        for (action, buttons) in rebind.get_profile().keymap {
+            for button in buttons.into_iter().filter_map(|b| b) {
+                script.push_str(&format!("bind {} +{}\n", quote(&button.to_string()), quote(action.name())));
+            }
+        }
+        for &(button, ref command) in &self.commands {
+            script.push_str(&format!("bind {} {}\n", quote(&button.to_string()), quote(command)));
+        }
+        script
+    }
+
+    fn run_line<S>(&mut self, rebind: &mut InputRebind<A, S>, line: &str, output: &mut ConsoleOutput<A>)
+                   -> Result<(), ConsoleError>
+        where S: BuildHasher + Default
+    {
+        for args in tokenize(line) {
+            self.run(rebind, &args, output)?;
+        }
+        Ok(())
+    }
+
+    fn run<S>(&mut self, rebind: &mut InputRebind<A, S>, args: &[String], output: &mut ConsoleOutput<A>)
+              -> Result<(), ConsoleError>
+        where S: BuildHasher + Default
+    {
+        let command = &args[0][..];
+        let args = &args[1..];
+        match command {
+            "bind" if args.len() == 1 => {
+                let binding = parse_binding(&args[0])?;
+                let bound = rebind.keymap.iter().find(|&(_, bt)| bt.contains(binding));
+                output.lines.push(match (bound, self.get_bound_command(binding)) {
+                    (Some((action, _)), _) => format!("\"{}\" = \"+{}\"", binding, action.name()),
+                    (None, Some(command)) => format!("\"{}\" = {}", binding, quote_always(command)),
+                    (None, None) => format!("\"{}\" is not bound", binding),
+                });
+            }
+            "bind" if args.len() == 2 && args[1].starts_with('+') => {
+                let binding = parse_binding(&args[0])?;
+                let name = &args[1][1..];
+                let action = parse_action::<A>(name)?;
+                let buttons = rebind.keymap.get(&action).cloned().unwrap_or_default();
+                if buttons.contains(binding) {
+                    return Ok(());
+                }
+                if !(self.rebindable)(action) {
+                    return Err(ConsoleError::Locked(name.to_string()));
+                }
+                if buttons.num_buttons_set() == 3 {
+                    return Err(ConsoleError::TooManyBindings(name.to_string()));
+                }
+                self.unbind(rebind, binding)?;
+                rebind.keymap.entry(action).or_insert_with(ButtonTuple::new).insert_inplace(binding);
+            }
+            "bind" if args.len() == 2 => {
+                let binding = parse_binding(&args[0])?;
+                self.unbind(rebind, binding)?;
+                self.commands.push((binding, args[1].clone()));
+            }
+            "bind" => return Err(ConsoleError::Usage("bind <button> [+action | command]")),
+            "unbind" if args.len() == 1 => {
+                let binding = parse_binding(&args[0])?;
+                self.unbind(rebind, binding)?;
+            }
+            "unbind" => return Err(ConsoleError::Usage("unbind <button>")),
+            "unbindall" => {
+                let rebindable = self.rebindable;
+                for (_, buttons) in rebind.keymap.iter_mut().filter(|&(&a, _)| rebindable(a)) {
+                    *buttons = ButtonTuple::new();
+                }
+                self.commands.clear();
+            }
+            "bindlist" => {
+                for (action, buttons) in rebind.get_profile().keymap {
+                    for button in buttons.into_iter().filter_map(|b| b) {
+                        output.lines.push(format!("{} \"+{}\"", button, action.name()));
+                    }
+                }
+                for &(button, ref command) in &self.commands {
+                    output.lines.push(format!("{} {}", button, quote_always(command)));
+                }
+            }
+            "exec" if args.len() == 1 => self.exec(rebind, &args[0], output)?,
+            "exec" => return Err(ConsoleError::Usage("exec <script>")),
+            _ if command.starts_with('+') => {
+                let action = parse_action::<A>(&command[1..])?;
+                if !self.held.contains(&action) {
+                    self.held.push(action);
+                    output.actions.push(Translated::Press(action));
+                }
+            }
+            _ if command.starts_with('-') => {
+                let action = parse_action::<A>(&command[1..])?;
+                if let Some(i) = self.held.iter().position(|&a| a == action) {
+                    self.held.remove(i);
+                    output.actions.push(Translated::Release(action));
+                }
+            }
+            _ => {
+                let action = A::from_name(command).ok_or_else(|| ConsoleError::UnknownCommand(command.to_string()))?;
+                // Tapping an action which is held would release it
+                if !self.held.contains(&action) {
+                    output.actions.push(Translated::Press(action));
+                    output.actions.push(Translated::Release(action));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Remove the button from its action or command, unless its action can't be rebound.
+    fn unbind<S>(&mut self, rebind: &mut InputRebind<A, S>, binding: Binding) -> Result<(), ConsoleError>
+        where S: BuildHasher + Default
+    {
+        let locked = rebind.keymap.iter().find(|&(&a, bt)| bt.contains(binding) && !(self.rebindable)(a));
+        if let Some((action, _)) = locked {
+            return Err(ConsoleError::Conflict(binding.to_string(), action.name().to_string()));
+        }
+        let unbound = |b: Option<Binding>| if b == Some(binding) { None } else { b };
+        for buttons in rebind.keymap.values_mut() {
+            *buttons = ButtonTuple(unbound(buttons.0), unbound(buttons.1), unbound(buttons.2));
+        }
+        self.commands.retain(|&(b, _)| b != binding);
+        Ok(())
+    }
+
+    fn exec<S>(&mut self, rebind: &mut InputRebind<A, S>, name: &str, output: &mut ConsoleOutput<A>)
+               -> Result<(), ConsoleError>
+        where S: BuildHasher + Default
+    {
+        if self.executing.iter().any(|n| n == name) {
+            return Err(ConsoleError::RecursiveExec(name.to_string()));
+        }
+        let script = (self.loader.0)(name).ok_or_else(|| ConsoleError::ScriptNotFound(name.to_string()))?;
+        self.executing.push(name.to_string());
+        let mut result = Ok(());
+        for (i, line) in script.lines().enumerate() {
+            result = self.run_line(rebind, line, output);
+            if let Err(error) = result {
+                result = Err(ConsoleError::Script { name: name.to_string(), line: i + 1, error: Box::new(error) });
+                break;
+            }
+        }
+        self.executing.pop();
+        result
+    }
+}
+
+impl<A: NamedAction + ActionInfo> BindConsole<A> {
+    /// Make `bind`, `unbind` and `unbindall` honour `ActionInfo::is_rebindable`, as
+    /// `InputRebind::rebind_action` does. Binding a button to an action which can't be
+    /// rebound, or taking away a button which is bound to one, fails, and `unbindall`
+    /// leaves their buttons bound.
+    pub fn lock_actions(mut self) -> Self {
+        self.rebindable = |a: A| a.is_rebindable();
+        self
+    }
+}
+
+impl<A: NamedAction> Default for BindConsole<A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -0,0 +1,39 @@
+use rustc_serialize::hex::{FromHex, ToHex};
+use std::fmt::{Display, Formatter, Result};
+
+/// Identifies a model of controller, so that a controller can be recognised when it is
+/// reconnected with a different id. This is the 16 byte GUID used by SDL.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub struct ControllerGuid(pub [u8; 16]);
+
+impl ControllerGuid {
+    /// Parse a GUID written as 32 hexadecimal digits, as it is in SDL mapping files.
+    pub fn from_hex(hex: &str) -> Option<Self> {
+        match hex.from_hex() {
+            Ok(ref bytes) if bytes.len() == 16 => {
+                let mut guid = [0; 16];
+                guid.copy_from_slice(bytes);
+                Some(ControllerGuid(guid))
+            }
+            _ => None,
+        }
+    }
+}
+
+impl Display for ControllerGuid {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", self.0.to_hex())
+    }
+}
+
+/// A controller being connected or disconnected. Piston doesn't report these, so they have
+/// to be passed on from the window backend, for example from the `ControllerDeviceAdded`
+/// and `ControllerDeviceRemoved` events of SDL.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ControllerEvent {
+    /// The controller with the given id and GUID has been connected.
+    Connected(i32, ControllerGuid),
+
+    /// The controller with the given id has been disconnected.
+    Disconnected(i32)
+}
@@ -0,0 +1,273 @@
+use {ControllerGuid, GamepadAxis, GamepadButton, GamepadMapping};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+
+/// A collection of controller mappings in the format of the SDL `gamecontrollerdb.txt`
+/// file, which is maintained by the community for many models of controller. Each line
+/// is a GUID, a name, and a list of elements such as `a:b0` or `leftx:a0`, which say
+/// which raw button or axis each button or axis of the standard gamepad is. An element
+/// `platform:Windows` restricts the mapping to one platform.
+///
+/// Only raw buttons (`b0`) and whole raw axes (`a0`) can be translated. Elements which
+/// use hats or half axes are skipped, as are elements for buttons which aren't part of
+/// the standard gamepad, and are listed in `GameControllerDbEntry::skipped`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameControllerDb {
+    entries: Vec<GameControllerDbEntry>,
+    platform: Option<String>
+}
+
+/// One line of a `GameControllerDb`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameControllerDbEntry {
+    /// The GUID of the model of controller.
+    pub guid: ControllerGuid,
+
+    /// The name of the model of controller.
+    pub name: String,
+
+    /// The platform which the mapping is for, or `None` if it is for every platform.
+    pub platform: Option<String>,
+
+    /// The mapping of the raw buttons and axes onto the standard gamepad.
+    pub mapping: GamepadMapping,
+
+    /// The elements which are valid but can't be translated, such as `dpup:h0.1`, which
+    /// uses a hat, and so aren't in the mapping.
+    pub skipped: Vec<String>
+}
+
+/// A line of a mapping file which couldn't be read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameControllerDbError {
+    /// The number of the line, starting from 1.
+    pub line: usize,
+
+    /// What is wrong with the line.
+    pub kind: GameControllerDbErrorKind
+}
+
+/// What is wrong with a line of a mapping file.
+#[derive(Clone, Debug, PartialEq)]
+pub enum GameControllerDbErrorKind {
+    /// The line doesn't start with a GUID of 32 hexadecimal digits.
+    InvalidGuid(String),
+
+    /// The line doesn't have a name after the GUID.
+    MissingName,
+
+    /// An element isn't of the form `name:value`, or its value isn't a raw button, axis or
+    /// hat.
+    InvalidElement(String)
+}
+
+impl Display for GameControllerDbError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match self.kind {
+            GameControllerDbErrorKind::InvalidGuid(ref guid) => {
+                write!(f, "line {}: invalid controller GUID `{}`", self.line, guid)
+            }
+            GameControllerDbErrorKind::MissingName => write!(f, "line {}: missing controller name", self.line),
+            GameControllerDbErrorKind::InvalidElement(ref element) => {
+                write!(f, "line {}: invalid mapping element `{}`", self.line, element)
+            }
+        }
+    }
+}
+
+impl Error for GameControllerDbError { }
+
+impl GameControllerDb {
+    /// Creates an empty collection, which looks up the mappings for the platform that the
+    /// program was compiled for.
+    pub fn new() -> Self {
+        let platform = if cfg!(target_os = "windows") {
+            Some("Windows")
+        } else if cfg!(target_os = "macos") {
+            Some("Mac OS X")
+        } else if cfg!(target_os = "linux") {
+            Some("Linux")
+        } else if cfg!(target_os = "android") {
+            Some("Android")
+        } else if cfg!(target_os = "ios") {
+            Some("iOS")
+        } else {
+            None
+        };
+        GameControllerDb { entries: vec![], platform: platform.map(String::from) }
+    }
+
+    /// Read the mappings in the text, which is in the format of `gamecontrollerdb.txt`.
+    /// Blank lines and lines starting with `#` are ignored. A mapping replaces any earlier
+    /// one for the same GUID and platform. Lines which can't be read are skipped, and an
+    /// error is returned for each of them.
+    pub fn add_mappings(&mut self, text: &str) -> Vec<GameControllerDbError> {
+        let mut errors = vec![];
+        for (i, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            match parse_entry(line) {
+                Ok(entry) => self.insert(entry),
+                Err(kind) => errors.push(GameControllerDbError { line: i + 1, kind: kind }),
+            }
+        }
+        errors
+    }
+
+    /// Add a mapping, replacing any earlier one for the same GUID and platform.
+    pub fn insert(&mut self, entry: GameControllerDbEntry) {
+        self.entries.retain(|e| e.guid != entry.guid || e.platform != entry.platform);
+        self.entries.push(entry);
+    }
+
+    /// Set the platform whose mappings are looked up. Mappings without a platform are used
+    /// for every platform. `None` uses the mappings for any platform.
+    pub fn set_platform(&mut self, platform: Option<&str>) {
+        self.platform = platform.map(String::from);
+    }
+
+    /// Returns the platform whose mappings are looked up.
+    pub fn get_platform(&self) -> Option<&str> {
+        self.platform.as_ref().map(|p| &p[..])
+    }
+
+    /// Returns the mapping for the controller on the platform, preferring one which names
+    /// the platform over one which doesn't.
+    pub fn get(&self, guid: ControllerGuid) -> Option<&GameControllerDbEntry> {
+        let mut matching = self.entries.iter().filter(|e| e.guid == guid).filter(|e| {
+            match (&self.platform, &e.platform) {
+                (&Some(ref platform), &Some(ref entry_platform)) => platform == entry_platform,
+                _ => true,
+            }
+        });
+        let first = matching.next();
+        match first {
+            Some(&GameControllerDbEntry { platform: None, .. }) => matching.find(|e| e.platform.is_some()).or(first),
+            _ => first,
+        }
+    }
+
+    /// Returns the gamepad mapping for the controller on the platform.
+    pub fn get_mapping(&self, guid: ControllerGuid) -> Option<&GamepadMapping> {
+        self.get(guid).map(|e| &e.mapping)
+    }
+
+    /// Returns the number of mappings.
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    /// Returns true if there aren't any mappings.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+}
+
+impl Default for GameControllerDb {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+enum Element {
+    Button(GamepadButton),
+    Axis(GamepadAxis),
+    Trigger(GamepadButton, GamepadAxis)
+}
+
+fn element(name: &str) -> Option<Element> {
+    use self::Element::*;
+
+    Some(match name {
+        "a" => Button(GamepadButton::South),
+        "b" => Button(GamepadButton::East),
+        "x" => Button(GamepadButton::West),
+        "y" => Button(GamepadButton::North),
+        "back" => Button(GamepadButton::Back),
+        "guide" => Button(GamepadButton::Guide),
+        "start" => Button(GamepadButton::Start),
+        "leftstick" => Button(GamepadButton::LeftStick),
+        "rightstick" => Button(GamepadButton::RightStick),
+        "leftshoulder" => Button(GamepadButton::LeftShoulder),
+        "rightshoulder" => Button(GamepadButton::RightShoulder),
+        "dpup" => Button(GamepadButton::DPadUp),
+        "dpdown" => Button(GamepadButton::DPadDown),
+        "dpleft" => Button(GamepadButton::DPadLeft),
+        "dpright" => Button(GamepadButton::DPadRight),
+        "leftx" => Axis(GamepadAxis::LeftX),
+        "lefty" => Axis(GamepadAxis::LeftY),
+        "rightx" => Axis(GamepadAxis::RightX),
+        "righty" => Axis(GamepadAxis::RightY),
+        "lefttrigger" => Trigger(GamepadButton::LeftTrigger, GamepadAxis::LeftTrigger),
+        "righttrigger" => Trigger(GamepadButton::RightTrigger, GamepadAxis::RightTrigger),
+        _ => return None,
+    })
+}
+
+fn parse_entry(line: &str) -> Result<GameControllerDbEntry, GameControllerDbErrorKind> {
+    let mut fields = line.split(',');
+    let guid = fields.next().unwrap_or("").trim();
+    let guid = match ControllerGuid::from_hex(guid) {
+        Some(g) => g,
+        None => return Err(GameControllerDbErrorKind::InvalidGuid(guid.to_string())),
+    };
+    let name = match fields.next().map(str::trim) {
+        Some(name) if !name.is_empty() => name.to_string(),
+        _ => return Err(GameControllerDbErrorKind::MissingName),
+    };
+
+    let mut mapping = GamepadMapping::new();
+    let mut platform = None;
+    let mut skipped = vec![];
+    for field in fields.map(str::trim).filter(|f| !f.is_empty()) {
+        let invalid = || GameControllerDbErrorKind::InvalidElement(field.to_string());
+        let mut parts = field.splitn(2, ':');
+        let (key, value) = match (parts.next(), parts.next()) {
+            (Some(key), Some(value)) if !key.is_empty() && !value.is_empty() => (key, value),
+            _ => return Err(invalid()),
+        };
+        if key == "platform" {
+            platform = Some(value.to_string());
+            continue;
+        }
+
+        // Half axes and inverted axes are valid, but can't be translated
+        let whole = !key.starts_with('+') && !key.starts_with('-') && !value.starts_with('+') &&
+                    !value.starts_with('-') && !value.ends_with('~');
+        let value = value.trim_matches(|c| c == '+' || c == '-' || c == '~');
+        if value.is_empty() || !value.is_char_boundary(1) {
+            return Err(invalid());
+        }
+        let (kind, index) = value.split_at(1);
+        let valid = match kind {
+            "b" | "a" => index.parse::<u8>().is_ok(),
+            "h" => {
+                let mut hat = index.splitn(2, '.');
+                hat.next().map_or(false, |h| h.parse::<u8>().is_ok()) &&
+                hat.next().map_or(false, |m| m.parse::<u8>().is_ok())
+            }
+            _ => false,
+        };
+        if !valid {
+            return Err(invalid());
+        }
+
+        match (element(key), kind, index.parse::<u8>()) {
+            (Some(Element::Button(b)), "b", Ok(i)) |
+            (Some(Element::Trigger(b, _)), "b", Ok(i)) => mapping = mapping.with_button(i, b),
+            (Some(Element::Axis(a)), "a", Ok(i)) |
+            (Some(Element::Trigger(_, a)), "a", Ok(i)) if whole => mapping = mapping.with_axis(i, a),
+            _ => skipped.push(field.to_string()),
+        }
+    }
+
+    Ok(GameControllerDbEntry {
+        guid: guid,
+        name: name,
+        platform: platform,
+        mapping: mapping,
+        skipped: skipped
+    })
+}
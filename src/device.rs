@@ -0,0 +1,36 @@
+use callback::Shared;
+use input::{Button, Input, Motion};
+
+/// A kind of device which input can come from. This is useful for showing button prompts
+/// for the device which the player is using.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum DeviceClass {
+    /// A keyboard.
+    Keyboard,
+    /// A mouse.
+    Mouse,
+    /// A gamepad or other controller.
+    Gamepad
+}
+
+impl DeviceClass {
+    /// Returns the class of device which produced the input, if the input shows that the
+    /// player is using it. Releases don't count, and neither does a controller axis whose
+    /// position is within `analog_noise_threshold` of the centre, so that a drifting stick
+    /// isn't mistaken for the player picking up the controller.
+    pub fn of_input(input: &Input, analog_noise_threshold: f64) -> Option<Self> {
+        match input {
+            &Input::Press(Button::Keyboard(_)) => Some(DeviceClass::Keyboard),
+            &Input::Press(Button::Mouse(_)) => Some(DeviceClass::Mouse),
+            &Input::Press(Button::Controller(_)) => Some(DeviceClass::Gamepad),
+            &Input::Move(Motion::ControllerAxis(args)) => {
+                if args.position.abs() > analog_noise_threshold { Some(DeviceClass::Gamepad) } else { None }
+            }
+            &Input::Move(_) => Some(DeviceClass::Mouse),
+            _ => None,
+        }
+    }
+}
+
+/// The callback which is notified when the last used device class changes.
+pub type DeviceClassCallback = Shared<dyn Fn(DeviceClass) + Send + Sync>;
@@ -0,0 +1,20 @@
+use callback::Shared;
+use input::Button;
+
+/// An unusual condition which was detected and corrected while translating input. These
+/// are reported through the callback set with `Builder::diagnostics_callback`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Diagnostic {
+    /// A button was held for longer than the maximum hold time, and was released
+    /// automatically. This usually means that its release event was lost.
+    StuckButton {
+        /// The button which was released.
+        button: Button,
+
+        /// How long the button had been held for, in seconds.
+        held_for: f64
+    }
+}
+
+/// The callback which is notified of diagnostics.
+pub type DiagnosticsCallback = Shared<dyn Fn(&Diagnostic) + Send + Sync>;
@@ -0,0 +1,78 @@
+use {Action, Translated};
+use input::Button;
+
+/// Recognises drags of the mouse buttons which are bound to actions. A drag starts once the
+/// cursor has moved further than the drag threshold from where the button was pressed.
+#[derive(Clone, Debug, PartialEq)]
+pub struct DragTracker<A: Action> {
+    drags: Vec<Drag<A>>
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+struct Drag<A: Action> {
+    button: Button,
+    action: A,
+    origin: Option<[f64; 2]>,
+    last: Option<[f64; 2]>,
+    dragging: bool
+}
+
+impl<A: Action> DragTracker<A> {
+    pub fn new() -> Self {
+        DragTracker { drags: vec![] }
+    }
+
+    /// Start tracking a button which has just been pressed at the cursor position.
+    pub fn begin(&mut self, button: Button, action: A, cursor: Option<[f64; 2]>) {
+        if !self.drags.iter().any(|d| d.button == button) {
+            self.drags.push(Drag {
+                button: button,
+                action: action,
+                origin: cursor,
+                last: cursor,
+                dragging: false
+            });
+        }
+    }
+
+    /// Update the tracked buttons with a new cursor position, returning any drag events
+    /// which it causes.
+    pub fn motion(&mut self, pos: [f64; 2], threshold: f64) -> Vec<Translated<A>> {
+        let mut translated = vec![];
+        for drag in self.drags.iter_mut() {
+            let origin = *drag.origin.get_or_insert(pos);
+            let last = drag.last.unwrap_or(origin);
+            drag.last = Some(pos);
+
+            if drag.dragging {
+                translated.push(Translated::DragUpdate(drag.action, pos, [pos[0] - last[0], pos[1] - last[1]]));
+            } else {
+                let delta = [pos[0] - origin[0], pos[1] - origin[1]];
+                if (delta[0] * delta[0] + delta[1] * delta[1]).sqrt() > threshold {
+                    drag.dragging = true;
+                    translated.push(Translated::DragStart(drag.action, origin));
+                    translated.push(Translated::DragUpdate(drag.action, pos, delta));
+                }
+            }
+        }
+        translated
+    }
+
+    /// Stop tracking a button which has been released. Returns a `DragEnd` if the button
+    /// was being dragged.
+    pub fn end(&mut self, button: Button) -> Option<Translated<A>> {
+        match self.drags.iter().position(|d| d.button == button) {
+            Some(i) => self.drags.remove(i).finish(),
+            None => None,
+        }
+    }
+}
+
+impl<A: Action> Drag<A> {
+    fn finish(self) -> Option<Translated<A>> {
+        match (self.dragging, self.last) {
+            (true, Some(pos)) => Some(Translated::DragEnd(self.action, pos)),
+            _ => None,
+        }
+    }
+}
@@ -0,0 +1,244 @@
+use {CalibrationStore, ControllerGuid, DeviceCalibration, GameControllerDb};
+use input::{Button, ControllerAxisArgs};
+use std::sync::Arc;
+
+/// A button of a standard gamepad, named by its position rather than its label, so that a
+/// binding works the same way across controllers whose buttons are labelled differently.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GamepadButton {
+    /// The bottom face button, such as A on an Xbox controller or Cross on a PlayStation
+    /// controller.
+    South,
+    /// The right face button.
+    East,
+    /// The left face button.
+    West,
+    /// The top face button.
+    North,
+    /// The left shoulder button.
+    LeftShoulder,
+    /// The right shoulder button.
+    RightShoulder,
+    /// The left trigger, on controllers which report it as a button.
+    LeftTrigger,
+    /// The right trigger, on controllers which report it as a button.
+    RightTrigger,
+    /// Pressing the left stick.
+    LeftStick,
+    /// Pressing the right stick.
+    RightStick,
+    /// Up on the d-pad.
+    DPadUp,
+    /// Down on the d-pad.
+    DPadDown,
+    /// Left on the d-pad.
+    DPadLeft,
+    /// Right on the d-pad.
+    DPadRight,
+    /// The start or menu button.
+    Start,
+    /// The back, select or view button.
+    Back,
+    /// The button with the logo of the platform on it.
+    Guide
+}
+
+impl GamepadButton {
+    /// Returns an identifier of the glyph which shows the button, such as `"face_south"`,
+    /// so that the UI can pick the icon from the icon set of the player's controller, such
+    /// as A on an Xbox controller or Cross on a PlayStation controller. The glyph of a raw
+    /// controller button is returned by `InputTranslator::get_glyph_id`.
+    pub fn glyph_id(&self) -> &'static str {
+        match *self {
+            GamepadButton::South => "face_south",
+            GamepadButton::East => "face_east",
+            GamepadButton::West => "face_west",
+            GamepadButton::North => "face_north",
+            GamepadButton::LeftShoulder => "shoulder_left",
+            GamepadButton::RightShoulder => "shoulder_right",
+            GamepadButton::LeftTrigger => "trigger_left",
+            GamepadButton::RightTrigger => "trigger_right",
+            GamepadButton::LeftStick => "stick_left",
+            GamepadButton::RightStick => "stick_right",
+            GamepadButton::DPadUp => "dpad_up",
+            GamepadButton::DPadDown => "dpad_down",
+            GamepadButton::DPadLeft => "dpad_left",
+            GamepadButton::DPadRight => "dpad_right",
+            GamepadButton::Start => "start",
+            GamepadButton::Back => "back",
+            GamepadButton::Guide => "guide",
+        }
+    }
+}
+
+/// An axis of a standard gamepad.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GamepadAxis {
+    /// The horizontal axis of the left stick.
+    LeftX,
+    /// The vertical axis of the left stick.
+    LeftY,
+    /// The horizontal axis of the right stick.
+    RightX,
+    /// The vertical axis of the right stick.
+    RightY,
+    /// The left trigger, on controllers which report it as an axis.
+    LeftTrigger,
+    /// The right trigger, on controllers which report it as an axis.
+    RightTrigger
+}
+
+/// Maps the raw button and axis indices of a model of controller onto the standard gamepad.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct GamepadMapping {
+    buttons: Vec<(u8, GamepadButton)>,
+    axes: Vec<(u8, GamepadAxis)>
+}
+
+impl GamepadMapping {
+    /// Creates a mapping without any buttons or axes.
+    pub fn new() -> Self {
+        Default::default()
+    }
+
+    /// The numbering used by the game controller API of SDL, which presents every
+    /// controller it recognises as an Xbox controller.
+    pub fn sdl_game_controller() -> Self {
+        use self::GamepadButton::*;
+
+        let buttons = [South, East, West, North, Back, Guide, Start, LeftStick, RightStick, LeftShoulder,
+                       RightShoulder, DPadUp, DPadDown, DPadLeft, DPadRight];
+        let axes = [GamepadAxis::LeftX, GamepadAxis::LeftY, GamepadAxis::RightX, GamepadAxis::RightY,
+                    GamepadAxis::LeftTrigger, GamepadAxis::RightTrigger];
+        let mapping = buttons.iter().enumerate().fold(GamepadMapping::new(), |m, (i, &b)| m.with_button(i as u8, b));
+        axes.iter().enumerate().fold(mapping, |m, (i, &a)| m.with_axis(i as u8, a))
+    }
+
+    /// Map the raw button index to the gamepad button, replacing any previous mapping of
+    /// the index.
+    pub fn with_button(mut self, raw: u8, button: GamepadButton) -> Self {
+        self.buttons.retain(|&(r, _)| r != raw);
+        self.buttons.push((raw, button));
+        self
+    }
+
+    /// Map the raw axis index to the gamepad axis, replacing any previous mapping of the
+    /// index.
+    pub fn with_axis(mut self, raw: u8, axis: GamepadAxis) -> Self {
+        self.axes.retain(|&(r, _)| r != raw);
+        self.axes.push((raw, axis));
+        self
+    }
+
+    /// Returns the gamepad button which the raw button index is mapped to.
+    pub fn get_button(&self, raw: u8) -> Option<GamepadButton> {
+        self.buttons.iter().find(|&&(r, _)| r == raw).map(|&(_, b)| b)
+    }
+
+    /// Returns the gamepad axis which the raw axis index is mapped to.
+    pub fn get_axis(&self, raw: u8) -> Option<GamepadAxis> {
+        self.axes.iter().find(|&&(r, _)| r == raw).map(|&(_, a)| a)
+    }
+
+    /// Returns the raw button index which is mapped to the gamepad button.
+    pub fn get_raw_button(&self, button: GamepadButton) -> Option<u8> {
+        self.buttons.iter().find(|&&(_, b)| b == button).map(|&(r, _)| r)
+    }
+
+    /// Returns the raw axis index which is mapped to the gamepad axis.
+    pub fn get_raw_axis(&self, axis: GamepadAxis) -> Option<u8> {
+        self.axes.iter().find(|&&(_, a)| a == axis).map(|&(r, _)| r)
+    }
+}
+
+/// The gamepad mappings used by a translator, and how they are applied.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GamepadLayout {
+    pub default_mapping: Option<GamepadMapping>,
+    pub controllers: Vec<(i32, GamepadMapping)>,
+    pub database: Option<Arc<GameControllerDb>>,
+    pub calibrations: Vec<(i32, DeviceCalibration)>,
+    pub calibration_store: Option<Arc<CalibrationStore>>,
+    pub swap_confirm_cancel: bool
+}
+
+impl GamepadLayout {
+    pub fn new() -> Self {
+        GamepadLayout {
+            default_mapping: None,
+            controllers: vec![],
+            database: None,
+            calibrations: vec![],
+            calibration_store: None,
+            swap_confirm_cancel: false
+        }
+    }
+
+    /// Returns the mapping used for the controller.
+    pub fn mapping(&self, controller_id: i32) -> Option<&GamepadMapping> {
+        match self.controllers.iter().find(|&&(id, _)| id == controller_id) {
+            Some(&(_, ref mapping)) => Some(mapping),
+            None => self.default_mapping.as_ref(),
+        }
+    }
+
+    /// Set the mapping used for the controller, or use the default mapping for it if `None`.
+    pub fn set_mapping(&mut self, controller_id: i32, mapping: Option<GamepadMapping>) {
+        self.controllers.retain(|&(id, _)| id != controller_id);
+        if let Some(mapping) = mapping {
+            self.controllers.push((controller_id, mapping));
+        }
+    }
+
+    /// Use the mapping from the database and the calibration from the store for a
+    /// controller which has been connected, if there are any for its GUID. Otherwise the
+    /// controller uses the default mapping and no calibration, rather than those left from a
+    /// controller which had the same id before.
+    pub fn controller_connected(&mut self, controller_id: i32, guid: ControllerGuid) {
+        let mapping = self.database.as_ref().and_then(|db| db.get_mapping(guid)).cloned();
+        self.set_mapping(controller_id, mapping);
+        let calibration = self.calibration_store.as_ref().and_then(|store| store.get(guid)).cloned();
+        self.set_calibration(controller_id, calibration);
+    }
+
+    /// Forget the mapping and calibration of a controller which has been disconnected.
+    pub fn controller_disconnected(&mut self, controller_id: i32) {
+        self.set_mapping(controller_id, None);
+        self.set_calibration(controller_id, None);
+    }
+
+    /// Returns the calibration of the controller.
+    pub fn calibration(&self, controller_id: i32) -> Option<&DeviceCalibration> {
+        self.calibrations.iter().find(|&&(id, _)| id == controller_id).map(|&(_, ref c)| c)
+    }
+
+    /// Set the calibration of the controller. `None` removes it.
+    pub fn set_calibration(&mut self, controller_id: i32, calibration: Option<DeviceCalibration>) {
+        self.calibrations.retain(|&(id, _)| id != controller_id);
+        if let Some(calibration) = calibration {
+            self.calibrations.push((controller_id, calibration));
+        }
+    }
+
+    /// Correct an axis motion with the calibration of its controller.
+    pub fn calibrate(&self, args: ControllerAxisArgs) -> ControllerAxisArgs {
+        match self.calibration(args.id) {
+            Some(calibration) => calibration.apply(args),
+            None => args,
+        }
+    }
+
+    /// Returns the gamepad button which a raw controller button is, after swapping the
+    /// confirm and cancel buttons if that is enabled.
+    pub fn button(&self, button: Button) -> Option<GamepadButton> {
+        let gamepad_button = match button {
+            Button::Controller(b) => self.mapping(b.id).and_then(|m| m.get_button(b.button)),
+            _ => None,
+        };
+        match gamepad_button {
+            Some(GamepadButton::South) if self.swap_confirm_cancel => Some(GamepadButton::East),
+            Some(GamepadButton::East) if self.swap_confirm_cancel => Some(GamepadButton::South),
+            b => b,
+        }
+    }
+}
@@ -0,0 +1,189 @@
+use input::MouseButton;
+use std::f64::consts::PI;
+
+/// A direction which a gesture stroke can be drawn in, in translated cursor coordinates.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum GestureDirection {
+    /// Towards the top of the window.
+    Up,
+
+    /// Towards the bottom of the window.
+    Down,
+
+    /// Towards the left of the window.
+    Left,
+
+    /// Towards the right of the window.
+    Right
+}
+
+impl GestureDirection {
+    fn angle(&self) -> f64 {
+        match *self {
+            GestureDirection::Right => 0.0,
+            GestureDirection::Down => PI / 2.0,
+            GestureDirection::Left => PI,
+            GestureDirection::Up => -PI / 2.0,
+        }
+    }
+}
+
+/// A shape drawn with the cursor while the gesture button is held. Gestures can be bound
+/// to actions, and are translated into a `Press` followed immediately by a `Release` when
+/// the gesture button is released.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Gesture {
+    /// A single straight stroke.
+    Stroke(GestureDirection),
+
+    /// Two straight strokes, one after the other, such as an L shape.
+    Strokes(GestureDirection, GestureDirection),
+
+    /// A closed loop drawn clockwise.
+    ClockwiseCircle,
+
+    /// A closed loop drawn anticlockwise.
+    AnticlockwiseCircle
+}
+
+/// Settings which control how gestures are recognised.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GestureSettings {
+    pub button: Option<MouseButton>,
+    pub min_stroke_length: f64,
+    pub direction_tolerance: f64
+}
+
+impl GestureSettings {
+    pub fn new() -> Self {
+        GestureSettings {
+            button: None,
+            min_stroke_length: 50.0,
+            direction_tolerance: PI / 6.0
+        }
+    }
+}
+
+/// Records the path of the cursor while the gesture button is held.
+#[derive(Clone, Debug, PartialEq)]
+pub struct GestureTracker {
+    points: Vec<[f64; 2]>,
+    active: bool
+}
+
+impl GestureTracker {
+    pub fn new() -> Self {
+        GestureTracker {
+            points: vec![],
+            active: false
+        }
+    }
+
+    /// Start recording a gesture at the cursor position.
+    pub fn begin(&mut self, cursor: Option<[f64; 2]>) {
+        self.points.clear();
+        self.points.extend(cursor);
+        self.active = true;
+    }
+
+    /// Record a new cursor position. Positions which are closer than a quarter of the
+    /// minimum stroke length to the previous one are skipped, to filter out jitter.
+    pub fn motion(&mut self, pos: [f64; 2], settings: &GestureSettings) {
+        if !self.active {
+            return;
+        }
+        let spacing = (settings.min_stroke_length / 4.0).max(1.0);
+        match self.points.last() {
+            Some(&last) if distance(last, pos) < spacing => {}
+            _ => self.points.push(pos),
+        }
+    }
+
+    /// Finish recording, returning the gesture which was drawn, if any.
+    pub fn end(&mut self, cursor: Option<[f64; 2]>, settings: &GestureSettings) -> Option<Gesture> {
+        if !self.active {
+            return None;
+        }
+        self.active = false;
+        if let Some(pos) = cursor {
+            if self.points.last() != Some(&pos) {
+                self.points.push(pos);
+            }
+        }
+        let gesture = recognise(&self.points, settings);
+        self.points.clear();
+        gesture
+    }
+
+    /// Stop recording without recognising a gesture.
+    pub fn cancel(&mut self) {
+        self.points.clear();
+        self.active = false;
+    }
+}
+
+fn distance(p0: [f64; 2], p1: [f64; 2]) -> f64 {
+    ((p1[0] - p0[0]).powi(2) + (p1[1] - p0[1]).powi(2)).sqrt()
+}
+
+/// Wrap an angle into the range `(-PI, PI]`.
+fn normalise(angle: f64) -> f64 {
+    let mut angle = angle % (2.0 * PI);
+    if angle <= -PI {
+        angle += 2.0 * PI;
+    } else if angle > PI {
+        angle -= 2.0 * PI;
+    }
+    angle
+}
+
+fn quantise(angle: f64, tolerance: f64) -> Option<GestureDirection> {
+    [GestureDirection::Up, GestureDirection::Down, GestureDirection::Left, GestureDirection::Right]
+        .iter()
+        .cloned()
+        .find(|d| normalise(angle - d.angle()).abs() <= tolerance)
+}
+
+fn recognise(points: &[[f64; 2]], settings: &GestureSettings) -> Option<Gesture> {
+    // A path without any segments, such as when the cursor position isn't known yet
+    if points.len() < 2 {
+        return None;
+    }
+
+    // (angle, length) of each segment of the path
+    let segments: Vec<(f64, f64)> = points.windows(2)
+                                          .map(|w| ((w[1][1] - w[0][1]).atan2(w[1][0] - w[0][0]), distance(w[0], w[1])))
+                                          .filter(|&(_, len)| len > 0.0)
+                                          .collect();
+
+    let total_length = segments.iter().fold(0.0, |acc, &(_, len)| acc + len);
+    if total_length < settings.min_stroke_length {
+        return None;
+    }
+
+    // The cursor y axis points down, so a positive turn is clockwise on screen
+    let turning = segments.windows(2).fold(0.0, |acc, w| acc + normalise(w[1].0 - w[0].0));
+    let closure = distance(points[0], points[points.len() - 1]);
+    if turning.abs() >= 1.5 * PI && closure <= total_length * 0.25 {
+        return Some(if turning > 0.0 { Gesture::ClockwiseCircle } else { Gesture::AnticlockwiseCircle });
+    }
+
+    let mut strokes: Vec<(GestureDirection, f64)> = vec![];
+    for &(angle, len) in &segments {
+        if let Some(dir) = quantise(angle, settings.direction_tolerance) {
+            match strokes.last_mut() {
+                Some(&mut (last, ref mut stroke_len)) if last == dir => *stroke_len += len,
+                _ => strokes.push((dir, len)),
+            }
+        }
+    }
+
+    strokes.retain(|&(_, len)| len >= settings.min_stroke_length);
+    strokes.dedup_by_key(|s| s.0);
+
+    match strokes.len() {
+        1 => Some(Gesture::Stroke(strokes[0].0)),
+        2 => Some(Gesture::Strokes(strokes[0].0, strokes[1].0)),
+        _ => None,
+    }
+}
@@ -68,12 +68,38 @@ extern crate fnv;
 extern crate input;
 extern crate itertools;
 extern crate rustc_serialize;
+#[cfg(feature = "serde")]
+extern crate serde;
 extern crate viewport;
 extern crate window;
 
+mod action_info;
 mod builder;
+mod calibration;
+mod callback;
+mod chord;
+mod click;
+mod config;
+mod console;
+mod controller;
+mod controller_db;
+mod device;
+mod diagnostics;
+mod drag;
+mod gamepad;
+mod gesture;
+mod multiplayer;
+mod names;
+mod notation;
+mod profile;
+mod region;
+mod scroll;
+#[cfg(feature = "serde")]
+mod serialization;
+mod state;
+mod virtual_cursor;
 
-use input::{Button, Input, Motion};
+use input::{Button, ControllerAxisArgs, Input, Motion, MouseButton};
 use itertools::Itertools;
 use std::cmp::{Eq, Ord, PartialEq};
 use std::collections::HashMap;
@@ -82,16 +108,59 @@ use std::convert::Into;
 use std::default::Default;
 use std::fmt::{Debug, Formatter, Result};
 use std::hash::{Hash, BuildHasher};
+use std::sync::Arc;
 use viewport::Viewport;
 use window::Size;
 
+pub use action_info::{ActionInfo, RebindError};
 pub use builder::Builder;
+pub use calibration::{AxisCalibration, CalibrationStep, CalibrationStore, CalibrationStoreError, CalibrationWizard,
+                      DeviceCalibration};
+pub use chord::{Chord, Modifiers, Sequence};
+pub use config::{BindingConfig, BindingConfigError, BindingConfigErrorKind};
+pub use console::{BindConsole, ConsoleError, ConsoleOutput};
+pub use controller::{ControllerEvent, ControllerGuid};
+pub use controller_db::{GameControllerDb, GameControllerDbEntry, GameControllerDbError, GameControllerDbErrorKind};
+pub use device::DeviceClass;
+use callback::Shared;
+use device::DeviceClassCallback;
+pub use diagnostics::Diagnostic;
+pub use gamepad::{GamepadAxis, GamepadButton, GamepadMapping};
+use gamepad::GamepadLayout;
+pub use gesture::{Gesture, GestureDirection};
+use gesture::GestureSettings;
+pub use multiplayer::{Device, KeyboardHalf, PlayerRouter, Routed};
+pub use names::{ButtonNames, NameForm, StringTable};
+pub use notation::ParseBindingError;
+pub use profile::{MouseSettings, Profile, ProfileError};
+pub use region::{Region, RegionBinding};
+pub use scroll::ScrollDirection;
+use diagnostics::DiagnosticsCallback;
+use state::TranslationState;
+pub use virtual_cursor::VirtualCursor;
 
 /// Represents a logical action to be bound to a particular button press, e.g.
 /// jump, attack, or move forward. Needs to be hashable, as it is used as a
 /// lookup key when rebinding an action to a different button.
 pub trait Action: Copy + Hash + Ord { }
 
+/// An action whose values can be listed, and which has a stable name for each value, so
+/// that it can be shown in menus and written to configuration files. `#[derive(Action)]`
+/// from the `rebind_plugins` crate implements this, naming each variant in snake case
+/// (`MoveLeft` becomes `move_left`), unless it is renamed with `#[action(rename = "...")]`.
+pub trait NamedAction: Action + 'static {
+    /// Returns every action, in the order they are declared.
+    fn all() -> &'static [Self];
+
+    /// Returns the name of the action.
+    fn name(&self) -> &'static str;
+
+    /// Returns the action with the given name, if there is one.
+    fn from_name(name: &str) -> Option<Self> {
+        Self::all().iter().cloned().find(|a| a.name() == name)
+    }
+}
+
 /// A translated action.
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Translated<A: Action> {
@@ -104,32 +173,126 @@ pub enum Translated<A: Action> {
     /// A translated mouse motion. The logical origin of a translated MouseCursor event
     /// is in the top left corner of the window, and the logical scroll is non-natural.
     /// Relative events are unchanged for now.
-    Move(Motion)
+    Move(Motion),
+
+    /// A mouse button bound to an action has been pressed, and the cursor has moved
+    /// further than the drag threshold. Contains the position where the button was
+    /// pressed.
+    DragStart(A, [f64; 2]),
+
+    /// The cursor has moved during a drag. Contains the new cursor position, and the
+    /// distance moved since the previous `DragStart` or `DragUpdate`.
+    DragUpdate(A, [f64; 2], [f64; 2]),
+
+    /// The mouse button has been released at the end of a drag. Contains the last cursor
+    /// position.
+    DragEnd(A, [f64; 2]),
+
+    /// A mouse button has been clicked twice in quick succession, and the double click is
+    /// bound to an action. This follows the `Press` of the second click.
+    DoubleClick(A),
+
+    /// A mouse button has been clicked three times in quick succession, and the triple
+    /// click is bound to an action. This follows the `Press` of the third click.
+    TripleClick(A),
+
+    /// The controller with the given id has been connected, or reconnected.
+    ControllerConnected(i32),
+
+    /// The controller with the given id has been disconnected. This follows a `Release`
+    /// of every action which was held on it, and is a good time to pause the game.
+    ControllerDisconnected(i32)
 }
 
-/// A three-element tuple of `Option<Button>`. For simplicity, a maximum number of 3
+/// Something which can be bound to an action. This is either a physical button, or
+/// another kind of input which is treated as a button press when it is translated.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Binding {
+    /// A keyboard, mouse or controller button.
+    Button(Button),
+
+    /// A discrete step of the scroll wheel in a direction. Each step is translated into a
+    /// `Press` followed immediately by a `Release`. Scroll steps are accumulated between
+    /// events, and one event can produce several of them, so they are only translated by
+    /// `InputTranslator::translate_stateful`, not by `InputTranslator::translate`.
+    Scroll(ScrollDirection),
+
+    /// The second of two quick consecutive clicks of a mouse button. This is translated
+    /// into a `DoubleClick`. The clicks are timed by the time passed to
+    /// `InputTranslator::update`, which must be called every frame.
+    DoubleClick(MouseButton),
+
+    /// The third of three quick consecutive clicks of a mouse button. This is translated
+    /// into a `TripleClick`.
+    TripleClick(MouseButton),
+
+    /// A gesture drawn with the cursor while the gesture button is held. This is
+    /// translated into a `Press` followed immediately by a `Release`.
+    Gesture(Gesture),
+
+    /// A button of the standard gamepad. A controller button is translated through this if
+    /// the button itself isn't bound, and the gamepad mapping of its controller maps it.
+    Gamepad(GamepadButton),
+
+    /// A button which is pressed while exactly these modifier keys are held, such as
+    /// `Ctrl+S`, which is written as it is by `Chord`. A chord takes precedence over the
+    /// binding of the button itself. The modifier keys are only followed by
+    /// `InputTranslator::translate_stateful`, so chords aren't translated by
+    /// `InputTranslator::translate`.
+    Chord(Modifiers, Button)
+}
+
+impl From<Button> for Binding {
+    fn from(button: Button) -> Self {
+        Binding::Button(button)
+    }
+}
+
+impl From<ScrollDirection> for Binding {
+    fn from(direction: ScrollDirection) -> Self {
+        Binding::Scroll(direction)
+    }
+}
+
+impl From<Gesture> for Binding {
+    fn from(gesture: Gesture) -> Self {
+        Binding::Gesture(gesture)
+    }
+}
+
+impl From<GamepadButton> for Binding {
+    fn from(button: GamepadButton) -> Self {
+        Binding::Gamepad(button)
+    }
+}
+
+/// A three-element tuple of `Option<Binding>`. For simplicity, a maximum number of 3
 /// buttons can be bound to each action, and this is exposed through the `InputRebind`
 /// struct.
-#[derive(Clone, Copy, Debug, Default, Eq, Hash, PartialEq)]
-pub struct ButtonTuple(pub Option<Button>, pub Option<Button>, pub Option<Button>);
+///
+/// This used to hold `Button`s, before other kinds of binding existed. A
+/// `ButtonTuple<Button>` still converts into it, and `buttons` converts it back, for code
+/// written against the old type.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub struct ButtonTuple<B = Binding>(pub Option<B>, pub Option<B>, pub Option<B>);
 
-impl ButtonTuple {
+impl<B: Copy + PartialEq> ButtonTuple<B> {
     /// Creates a new tuple with no buttons in it (equivalent to `Default::default()`).
     pub fn new() -> Self {
         Default::default()
     }
 
     /// Check if the button is in the tuple.
-    pub fn contains(&self, button: Button) -> bool {
-        let sbtn = Some(button);
+    pub fn contains<T: Into<B>>(&self, button: T) -> bool {
+        let sbtn = Some(button.into());
         self.0 == sbtn || self.1 == sbtn || self.2 == sbtn
     }
 
     /// Insert a button into the tuple if there is room, searching from left to right.
     /// If the button is inserted, returns true. Otherwise, if the button is not inserted,
     /// this function returns false.
-    pub fn insert_inplace(&mut self, button: Button) -> bool {
-        let sbtn = Some(button);
+    pub fn insert_inplace<T: Into<B>>(&mut self, button: T) -> bool {
+        let sbtn = Some(button.into());
         match self {
             &mut ButtonTuple(None, _, _) => {
                 self.0 = sbtn;
@@ -158,14 +321,42 @@ impl ButtonTuple {
     }
 
     /// Returns an iterator over this tuple.
-    pub fn iter(&self) -> ButtonTupleIter {
+    pub fn iter(&self) -> ButtonTupleIter<B> {
         (*self).into_iter()
     }
 }
 
-impl IntoIterator for ButtonTuple {
-    type Item = Option<Button>;
-    type IntoIter = ButtonTupleIter;
+impl ButtonTuple {
+    /// Returns the bindings which are buttons, leaving out other kinds of binding, such as
+    /// scroll steps and gestures. The buttons keep their order, and are moved to the left.
+    pub fn buttons(&self) -> ButtonTuple<Button> {
+        let mut buttons = ButtonTuple::new();
+        for binding in self.iter().filter_map(|b| b) {
+            if let Binding::Button(button) = binding {
+                buttons.insert_inplace(button);
+            }
+        }
+        buttons
+    }
+}
+
+impl<B> Default for ButtonTuple<B> {
+    fn default() -> Self {
+        ButtonTuple(None, None, None)
+    }
+}
+
+impl From<ButtonTuple<Button>> for ButtonTuple {
+    fn from(buttons: ButtonTuple<Button>) -> Self {
+        ButtonTuple(buttons.0.map(Binding::Button),
+                    buttons.1.map(Binding::Button),
+                    buttons.2.map(Binding::Button))
+    }
+}
+
+impl<B: Copy> IntoIterator for ButtonTuple<B> {
+    type Item = Option<B>;
+    type IntoIter = ButtonTupleIter<B>;
 
     fn into_iter(self) -> Self::IntoIter {
         ButtonTupleIter { button_tuple: self, i: 0 }
@@ -174,13 +365,13 @@ impl IntoIterator for ButtonTuple {
 
 /// An iterator over a ButtonTuple.
 #[derive(Clone, Copy, Debug)]
-pub struct ButtonTupleIter {
-    button_tuple: ButtonTuple,
+pub struct ButtonTupleIter<B = Binding> {
+    button_tuple: ButtonTuple<B>,
     i: usize
 }
 
-impl Iterator for ButtonTupleIter {
-    type Item = Option<Button>;
+impl<B: Copy> Iterator for ButtonTupleIter<B> {
+    type Item = Option<B>;
 
     fn next(&mut self) -> Option<Self::Item> {
         let i = self.i;
@@ -194,17 +385,29 @@ impl Iterator for ButtonTupleIter {
     }
 }
 
-impl ExactSizeIterator for ButtonTupleIter {
+impl<B: Copy> ExactSizeIterator for ButtonTupleIter<B> {
     fn len(&self) -> usize {
         (std::cmp::min(self.i as isize, 3) - 3).abs() as usize
     }
 }
 
-/// An object which translates piston::input::Input events into input_map::Translated<A> events
-#[derive(Clone, Debug, PartialEq)]
+/// An object which translates piston::input::Input events into input_map::Translated<A> events.
+/// Two translators are equal if they have the same bindings and settings, whichever buttons
+/// are currently held.
+#[derive(Clone, Debug)]
 pub struct InputTranslator<A: Action, S: BuildHasher = RandomState> {
-    keymap: HashMap<Button, A, S>,
-    mouse_translator: MouseTranslator
+    keymap: HashMap<Binding, A, S>,
+    regions: Vec<RegionBinding<A>>,
+    mouse_translator: MouseTranslator,
+    button_data: ButtonTranslationData,
+    state: TranslationState<A>
+}
+
+impl<A: Action, S: BuildHasher> PartialEq for InputTranslator<A, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keymap == other.keymap && self.regions == other.regions &&
+        self.mouse_translator == other.mouse_translator && self.button_data == other.button_data
+    }
 }
 
 impl<A: Action, S: BuildHasher + Default> InputTranslator<A, S> {
@@ -212,15 +415,20 @@ impl<A: Action, S: BuildHasher + Default> InputTranslator<A, S> {
     pub fn new<Sz: Into<Size>>(size: Sz) -> Self {
         InputTranslator {
             keymap: HashMap::<_, _, S>::default(),
-            mouse_translator: MouseTranslator::new(size)
+            regions: vec![],
+            mouse_translator: MouseTranslator::new(size),
+            button_data: ButtonTranslationData::new(),
+            state: TranslationState::new()
         }
     }
 
     /// Translate an Input into a Translated<A> event. Returns `None` if there is no
-    /// action associated with the `Input` variant.
+    /// action associated with the `Input` variant. Bindings which need to remember earlier
+    /// input, such as scroll steps, clicks and gestures, are only translated by
+    /// `translate_stateful`.
     pub fn translate(&self, input: &Input) -> Option<Translated<A>> {
         macro_rules! translate_button(($but_state:ident, $but_var:ident) => (
-            match self.keymap.get(&$but_var).cloned() {
+            match self.button_action($but_var) {
                 Some(act) => Some(Translated::$but_state(act)),
                 None => None
             });
@@ -229,11 +437,598 @@ impl<A: Action, S: BuildHasher + Default> InputTranslator<A, S> {
         match input {
             &Input::Press(button) => translate_button!(Press, button),
             &Input::Release(button) => translate_button!(Release, button),
-            &Input::Move(motion) => Some(Translated::Move(self.mouse_translator.translate(motion))),
+            &Input::Move(motion) => Some(Translated::Move(self.mouse_translator.translate(self.calibrate(motion)))),
+            _ => None,
+        }
+    }
+
+    /// Translate an Input into `Translated<A>` events, keeping track of which actions are
+    /// currently held. Unlike `translate`, a `Release` is only produced for a button which
+    /// was pressed through this method, and when the window loses focus a `Release` is
+    /// emitted for every held action, as the real release events will never arrive. An
+    /// action which is held through several buttons is pressed by the first of them, and
+    /// released when the last of them is released, so its presses and releases pair up.
+    ///
+    /// Scroll wheel motion is accumulated into steps of `scroll_step`, and every step in
+    /// a direction which is bound to an action produces a `Press` and a `Release` after
+    /// the `Move` event. At most 16 steps are produced in each direction by one event. The
+    /// motion is also added to the scroll axis returned by `get_scroll_axis`.
+    ///
+    /// If a virtual cursor is attached, its position is reported in place of the real
+    /// cursor. The real cursor is only reported if the virtual cursor follows the mouse.
+    ///
+    /// If relative motion is synthesized, every motion of the mouse cursor after the first
+    /// produces a `Move(MouseRelative)` containing the distance moved in translated
+    /// coordinates, after the `Move(MouseCursor)`. It is measured from the real mouse
+    /// cursor, even if a virtual cursor is attached, and the virtual cursor moving by
+    /// itself doesn't produce any.
+    ///
+    /// Presses of a mouse button are first resolved against the region bindings using the
+    /// last known cursor position, and the `Release` is always for the same action as the
+    /// `Press`, even if the cursor has left the region.
+    ///
+    /// If a drag threshold is set, mouse buttons which are bound to actions also produce
+    /// `DragStart`, `DragUpdate` and `DragEnd` events when the cursor is moved while they
+    /// are held. The drag positions are in the same translated coordinates as `Move`.
+    ///
+    /// If a gesture button is set, the path of the cursor while it is held is recognised as
+    /// a `Gesture` when it is released, producing a `Press` and `Release` of the action
+    /// bound to that gesture after the `Release` of the button itself.
+    ///
+    /// Consecutive clicks of a mouse button are counted using the time passed to `update`,
+    /// and produce a `DoubleClick` or `TripleClick` if one is bound for that button. If
+    /// `update` isn't called, no time passes between clicks, so they all count as
+    /// consecutive.
+    ///
+    /// If `suppress_input_after_focus` is set, the first button press after the window
+    /// regains focus (and its matching release) is ignored. If `filter_repeated_presses`
+    /// is set, presses of a button which is already held (such as those generated by
+    /// the OS key repeat) are ignored.
+    ///
+    /// A `Release` for a held action whose binding was changed since it was pressed is
+    /// emitted before any other events.
+    pub fn translate_stateful(&mut self, input: &Input) -> Vec<Translated<A>> {
+        let calibrated = match input {
+            &Input::Move(motion @ Motion::ControllerAxis(_)) => Some(Input::Move(self.calibrate(motion))),
+            _ => None,
+        };
+        let input = calibrated.as_ref().unwrap_or(input);
+        let mut translated = self.pending_releases();
+        self.track_device_class(input);
+        translated.extend(self.translate_held(input));
+        translated
+    }
+
+    fn track_device_class(&mut self, input: &Input) {
+        // The first cursor position is only where the cursor happened to be, and the one after
+        // a warp is where the application put it
+        if let &Input::Move(Motion::MouseCursor(..)) = input {
+            if self.state.take_cursor_warp() || self.state.cursor().is_none() {
+                return;
+            }
+        }
+        match DeviceClass::of_input(input, self.button_data.analog_noise_threshold) {
+            Some(class) if self.state.last_device_class() != Some(class) => {
+                self.state.set_last_device_class(class);
+                if let Some(ref callback) = self.button_data.device_class_changed {
+                    (callback.0)(class);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn translate_held(&mut self, input: &Input) -> Vec<Translated<A>> {
+        match input {
+            &Input::Press(button) => {
+                if let Some(ref mut cursor) = self.state.virtual_cursor {
+                    cursor.button_changed(button, true);
+                }
+                self.state.modifier_changed(button, true);
+                if self.state.suppress_press(button) {
+                    return vec![];
+                }
+                if self.button_data.filter_repeated_presses && self.state.is_held(button) {
+                    self.state.repeat(button);
+                    return vec![];
+                }
+                if self.is_gesture_button(button) {
+                    let cursor = self.state.cursor();
+                    self.state.gestures.begin(cursor);
+                }
+                let mut translated = vec![];
+                let region_act = self.translate_region_press(button);
+                let act = region_act.or_else(|| self.chord_action(button)).or_else(|| self.button_action(button));
+                if let Some(act) = act {
+                    let newly_held = self.state.press(button, act);
+                    if let (Button::Mouse(_), Some(_)) = (button, self.mouse_translator.data.drag_threshold) {
+                        let cursor = self.state.cursor();
+                        self.state.begin_drag(button, act, cursor);
+                    }
+                    if newly_held {
+                        translated.push(Translated::Press(act));
+                    }
+                }
+                if let Button::Mouse(mouse_button) = button {
+                    translated.extend(self.translate_click(mouse_button));
+                }
+                translated
+            }
+            &Input::Release(button) => {
+                if let Some(ref mut cursor) = self.state.virtual_cursor {
+                    cursor.button_changed(button, false);
+                }
+                self.state.modifier_changed(button, false);
+                if self.state.suppress_release(button) {
+                    return vec![];
+                }
+                let mut translated = self.state.release(button);
+                if self.is_gesture_button(button) {
+                    let cursor = self.state.cursor();
+                    let gesture = self.state.gestures.end(cursor, &self.mouse_translator.data.gestures);
+                    if let Some(&act) = gesture.and_then(|g| self.keymap.get(&Binding::Gesture(g))) {
+                        translated.push(Translated::Press(act));
+                        translated.push(Translated::Release(act));
+                    }
+                }
+                translated
+            }
+            &Input::Move(motion) => {
+                match self.mouse_translator.translate(motion) {
+                    Motion::MouseCursor(x, y) => {
+                        // Relative motion is measured from the mouse, before a virtual cursor replaces it
+                        let relative = if self.mouse_translator.data.synthesize_relative_motion {
+                            self.state.mouse_motion([x, y]).map(|d| Translated::Move(Motion::MouseRelative(d[0], d[1])))
+                        } else {
+                            None
+                        };
+                        let followed = match self.state.virtual_cursor {
+                            Some(ref mut cursor) => cursor.mouse_moved([x, y]),
+                            None => Some([x, y]),
+                        };
+                        match followed {
+                            Some(pos) => {
+                                let mut translated = self.translate_cursor(pos);
+                                if let Some(relative) = relative {
+                                    translated.insert(1, relative);
+                                }
+                                translated
+                            }
+                            None => relative.into_iter().collect(),
+                        }
+                    }
+                    Motion::MouseScroll(x, y) => {
+                        let mut translated = vec![Translated::Move(Motion::MouseScroll(x, y))];
+                        let step = self.mouse_translator.data.scroll_step;
+                        for dir in self.state.scroll(x, y, step) {
+                            if let Some(&act) = self.keymap.get(&Binding::Scroll(dir)) {
+                                translated.push(Translated::Press(act));
+                                translated.push(Translated::Release(act));
+                            }
+                        }
+                        translated
+                    }
+                    Motion::ControllerAxis(args) => {
+                        if let Some(ref mut cursor) = self.state.virtual_cursor {
+                            cursor.axis_moved(args);
+                        }
+                        vec![Translated::Move(Motion::ControllerAxis(args))]
+                    }
+                    motion => vec![Translated::Move(motion)],
+                }
+            }
+            &Input::Cursor(inside) => {
+                self.state.set_cursor_inside(inside);
+                vec![]
+            }
+            &Input::Focus(focus) => {
+                if let (false, Some(cursor)) = (focus, self.state.virtual_cursor.as_mut()) {
+                    cursor.reset_input();
+                }
+                self.state.set_focus(focus, self.button_data.suppress_input_after_focus)
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Handle a movement of the cursor, in translated coordinates, whether it was moved by the
+    /// mouse or is a virtual cursor.
+    fn translate_cursor(&mut self, pos: [f64; 2]) -> Vec<Translated<A>> {
+        let mut translated = vec![Translated::Move(Motion::MouseCursor(pos[0], pos[1]))];
+        self.state.set_cursor(pos);
+        self.state.gestures.motion(pos, &self.mouse_translator.data.gestures);
+        if let Some(threshold) = self.mouse_translator.data.drag_threshold {
+            translated.extend(self.state.drag_motion(pos, threshold));
+        }
+        translated
+    }
+
+    fn translate_region_press(&self, button: Button) -> Option<A> {
+        match (button, self.state.cursor()) {
+            (Button::Mouse(b), Some(pos)) => region::resolve(&self.regions, b, pos),
+            _ => None,
+        }
+    }
+
+    fn calibrate(&self, motion: Motion) -> Motion {
+        match motion {
+            Motion::ControllerAxis(args) => Motion::ControllerAxis(self.button_data.gamepads.calibrate(args)),
+            motion => motion,
+        }
+    }
+
+    fn chord_action(&self, button: Button) -> Option<A> {
+        match self.state.modifiers() {
+            modifiers if modifiers == Modifiers::default() => None,
+            modifiers => self.keymap.get(&Binding::Chord(modifiers, button)).cloned(),
+        }
+    }
+
+    fn button_action(&self, button: Button) -> Option<A> {
+        match self.keymap.get(&Binding::Button(button)) {
+            Some(&act) => Some(act),
+            None => {
+                let gamepad_button = self.button_data.gamepads.button(button);
+                gamepad_button.and_then(|b| self.keymap.get(&Binding::Gamepad(b)).cloned())
+            }
+        }
+    }
+
+    fn is_gesture_button(&self, button: Button) -> bool {
+        match (button, self.mouse_translator.data.gestures.button) {
+            (Button::Mouse(b), Some(gesture_button)) => b == gesture_button,
+            _ => false,
+        }
+    }
+
+    fn translate_click(&mut self, button: MouseButton) -> Option<Translated<A>> {
+        let (time, cursor) = (self.state.time(), self.state.cursor());
+        let count = self.state.clicks.press(button,
+                                            time,
+                                            cursor,
+                                            self.mouse_translator.data.multi_click_interval,
+                                            self.mouse_translator.data.multi_click_distance);
+        match count {
+            2 => self.keymap.get(&Binding::DoubleClick(button)).map(|&a| Translated::DoubleClick(a)),
+            3 => self.keymap.get(&Binding::TripleClick(button)).map(|&a| Translated::TripleClick(a)),
             _ => None,
         }
     }
 
+    fn pending_releases(&mut self) -> Vec<Translated<A>> {
+        self.state.take_pending_releases()
+    }
+
+    /// Bind the button to the action, replacing the action which it was previously bound
+    /// to. If the button is currently held as a different action, that action is released.
+    pub fn insert_mapping<B: Into<Binding>>(&mut self, action: A, button: B) -> Vec<Translated<A>> {
+        self.keymap.insert(button.into(), action);
+        self.invalidate_held_bindings()
+    }
+
+    /// Remove the binding for the button, returning the action which it was bound to. If
+    /// the button is currently held, its action is released.
+    pub fn remove_mapping<B: Into<Binding>>(&mut self, button: B) -> (Option<A>, Vec<Translated<A>>) {
+        let action = self.keymap.remove(&button.into());
+        (action, self.invalidate_held_bindings())
+    }
+
+    fn invalidate_held_bindings(&mut self) -> Vec<Translated<A>> {
+        self.invalidate_held_bindings_later();
+        self.pending_releases()
+    }
+
+    fn invalidate_held_bindings_later(&mut self) {
+        let (keymap, regions, gamepads) = (&self.keymap, &self.regions, &self.button_data.gamepads);
+        self.state.invalidate_bindings(|b, a| {
+            let gamepad_bound = || match keymap.get(&Binding::Button(b)) {
+                Some(_) => false,
+                None => gamepads.button(b).and_then(|g| keymap.get(&Binding::Gamepad(g))) == Some(&a),
+            };
+            let chord_bound = || {
+                keymap.iter().any(|(k, &v)| match *k {
+                    Binding::Chord(_, button) => button == b && v == a,
+                    _ => false,
+                })
+            };
+            keymap.get(&Binding::Button(b)) == Some(&a) || gamepad_bound() || chord_bound() ||
+            regions.iter().any(|r| Button::Mouse(r.button) == b && r.action == a)
+        });
+    }
+
+    /// Add a region binding. Clicks of its button inside the region are translated into
+    /// its action, in preference to any binding of the button itself.
+    pub fn insert_region_mapping(&mut self, binding: RegionBinding<A>) {
+        self.regions.push(binding);
+    }
+
+    /// Remove every region binding for the action. If the action is currently held
+    /// through one of them, it is released.
+    pub fn remove_region_mappings(&mut self, action: A) -> Vec<Translated<A>> {
+        self.regions.retain(|r| r.action != action);
+        self.invalidate_held_bindings()
+    }
+
+    /// Returns the region bindings, in the order they were added.
+    pub fn get_region_mappings(&self) -> &[RegionBinding<A>] {
+        &self.regions
+    }
+
+    /// Advance the time used by `translate_stateful` by `dt` seconds, and reset the scroll
+    /// axis. This should be called on every update event. If a maximum hold time is set,
+    /// any button which has been held for longer than it is released, and reported to the
+    /// diagnostics callback as a `Diagnostic::StuckButton`. If a virtual cursor is
+    /// attached, it is moved, and a `Move(MouseCursor)` is produced if its position changed.
+    pub fn update(&mut self, dt: f64) -> Vec<Translated<A>> {
+        let mut translated = self.pending_releases();
+        self.state.reset_scroll_axis();
+        self.state.advance(dt);
+        if let Some(max) = self.button_data.max_hold_time {
+            if let Some(ref callback) = self.button_data.diagnostics {
+                for h in self.state.held().iter().filter(|h| h.held_for > max) {
+                    (callback.0)(&Diagnostic::StuckButton { button: h.button, held_for: h.held_for });
+                }
+            }
+            translated.extend(self.state.release_stuck(max));
+        }
+
+        let size = self.mouse_translator.data.viewport_size;
+        let moved = self.state.virtual_cursor.as_mut().and_then(|c| c.update(dt, size));
+        if let Some(pos) = moved {
+            translated.extend(self.translate_cursor(pos));
+        }
+        translated
+    }
+
+    /// Attach a virtual cursor, which replaces the real mouse cursor in the `Move` events
+    /// produced by `translate_stateful` and `update`. `None` detaches it.
+    pub fn set_virtual_cursor(&mut self, cursor: Option<VirtualCursor>) {
+        self.state.virtual_cursor = cursor;
+    }
+
+    /// Returns the attached virtual cursor.
+    pub fn get_virtual_cursor(&self) -> Option<&VirtualCursor> {
+        self.state.virtual_cursor.as_ref()
+    }
+
+    /// Returns the attached virtual cursor mutably, so that it can be moved.
+    pub fn get_virtual_cursor_mut(&mut self) -> Option<&mut VirtualCursor> {
+        self.state.virtual_cursor.as_mut()
+    }
+
+    /// Returns the last cursor position seen by `translate_stateful`, in translated
+    /// coordinates. Returns `None` if the cursor has not moved yet.
+    pub fn get_cursor_position(&self) -> Option<[f64; 2]> {
+        self.state.cursor()
+    }
+
+    /// Notify the translator that the cursor has been warped by the application, for
+    /// example to re-centre it for mouse look. The next cursor motion is treated as the
+    /// first one, so no relative motion is synthesized for the jump, and it doesn't count
+    /// as use of the mouse for `get_last_device_class`.
+    pub fn notify_cursor_warp(&mut self) {
+        self.state.warp_cursor();
+    }
+
+    /// Returns the distance which the scroll wheel has moved along each axis since the last
+    /// call to `update`, in steps of `scroll_step`, for continuous uses such as zooming.
+    /// Unlike the scroll bindings, this includes fractions of a step. Uses the scroll seen
+    /// by `translate_stateful`, after any inversion.
+    pub fn get_scroll_axis(&self) -> [f64; 2] {
+        self.state.scroll_axis()
+    }
+
+    /// Returns the edge scroll axis, for panning a camera when the cursor is near the edge
+    /// of the window. Each component is in the range `[-1, 1]`, with negative values
+    /// pointing left and up, and ramps up as the cursor moves from the inner edge of the
+    /// margin towards the edge of the window. The axis is zero if edge scrolling is
+    /// disabled, or if the cursor has left the window or the window has lost focus. Uses
+    /// the cursor position seen by `translate_stateful`.
+    pub fn get_edge_scroll(&self) -> [f64; 2] {
+        match self.state.active_cursor() {
+            Some(cursor) => self.mouse_translator.edge_scroll(cursor),
+            None => [0.0, 0.0],
+        }
+    }
+
+    /// Track a controller being connected or disconnected. When a controller is connected,
+    /// the mapping which the gamepad database has for its GUID is used for it, or the default
+    /// mapping if there isn't one, and likewise for the calibration store. When a controller
+    /// is disconnected, the actions held on it are released, its stick is centred if it
+    /// drives the virtual cursor, and its mapping and calibration are forgotten.
+    pub fn controller_event(&mut self, event: ControllerEvent) -> Vec<Translated<A>> {
+        let mut translated = self.pending_releases();
+        match event {
+            ControllerEvent::Connected(id, guid) => {
+                self.state.connect_controller(id, guid);
+                self.button_data.gamepads.controller_connected(id, guid);
+                translated.extend(self.invalidate_held_bindings());
+                translated.push(Translated::ControllerConnected(id));
+            }
+            ControllerEvent::Disconnected(id) => {
+                self.state.disconnect_controller(id);
+                if let Some(ref mut cursor) = self.state.virtual_cursor {
+                    cursor.controller_disconnected(id);
+                }
+                translated.extend(self.release_buttons(|b| match b {
+                    Button::Controller(c) => c.id == id,
+                    _ => false,
+                }));
+                self.button_data.gamepads.controller_disconnected(id);
+                translated.push(Translated::ControllerDisconnected(id));
+            }
+        }
+        translated
+    }
+
+    /// Returns the id and GUID of each controller which is connected, according to
+    /// `controller_event`.
+    pub fn get_controllers(&self) -> &[(i32, ControllerGuid)] {
+        self.state.controllers()
+    }
+
+    /// Release every held button which matches the predicate, such as all of the buttons of
+    /// a device which has been taken away from the player. Produces a `DragEnd` for any of
+    /// them which were being dragged, and a `Release` of each action which they were pressed
+    /// as, unless it is still held through a button which doesn't match.
+    pub fn release_buttons<F: Fn(Button) -> bool>(&mut self, matches: F) -> Vec<Translated<A>> {
+        let buttons: Vec<Button> = self.state.held().iter().map(|h| h.button).filter(|&b| matches(b)).collect();
+        if let Some(ref mut cursor) = self.state.virtual_cursor {
+            for button in buttons {
+                cursor.button_changed(button, false);
+            }
+        }
+        self.state.release_where(matches)
+    }
+
+    /// Returns true if the button is held according to `translate_stateful`.
+    pub fn is_held(&self, button: Button) -> bool {
+        self.state.is_held(button)
+    }
+
+    /// Set whether presses of a button which is already held are ignored by
+    /// `translate_stateful`.
+    pub fn set_filter_repeated_presses(&mut self, filter: bool) {
+        self.button_data.filter_repeated_presses = filter;
+    }
+
+    /// Returns true if presses of a button which is already held are ignored.
+    pub fn get_filter_repeated_presses(&self) -> bool {
+        self.button_data.filter_repeated_presses
+    }
+
+    /// Set the maximum time in seconds that a button may be held before it is released
+    /// automatically. A repeated press of a held button, such as from the OS key repeat,
+    /// restarts its hold time. `None` disables the limit.
+    pub fn set_max_hold_time(&mut self, max_hold_time: Option<f64>) {
+        self.button_data.max_hold_time = max_hold_time;
+    }
+
+    /// Returns the maximum time in seconds that a button may be held.
+    pub fn get_max_hold_time(&self) -> Option<f64> {
+        self.button_data.max_hold_time
+    }
+
+    /// Set the gamepad mapping used for the controller, in place of the default one, until
+    /// it is disconnected or connected again according to `controller_event`. `None` makes
+    /// the controller use the default mapping again. Any held button which is no longer
+    /// translated to the action it was pressed as is released.
+    pub fn set_gamepad_mapping(&mut self, controller_id: i32, mapping: Option<GamepadMapping>)
+                               -> Vec<Translated<A>> {
+        self.button_data.gamepads.set_mapping(controller_id, mapping);
+        self.invalidate_held_bindings()
+    }
+
+    /// Set the gamepad mapping used for controllers which don't have their own. Any held
+    /// button which is no longer translated to the action it was pressed as is released.
+    pub fn set_default_gamepad_mapping(&mut self, mapping: Option<GamepadMapping>) -> Vec<Translated<A>> {
+        self.button_data.gamepads.default_mapping = mapping;
+        self.invalidate_held_bindings()
+    }
+
+    /// Set the database of gamepad mappings, which are used for controllers as they are
+    /// connected, according to `controller_event`.
+    pub fn set_gamepad_database(&mut self, database: Option<GameControllerDb>) {
+        self.button_data.gamepads.database = database.map(Arc::new);
+    }
+
+    /// Returns the database of gamepad mappings.
+    pub fn get_gamepad_database(&self) -> Option<&GameControllerDb> {
+        self.button_data.gamepads.database.as_ref().map(|db| &**db)
+    }
+
+    /// Returns the gamepad mapping used for the controller.
+    pub fn get_gamepad_mapping(&self, controller_id: i32) -> Option<&GamepadMapping> {
+        self.button_data.gamepads.mapping(controller_id)
+    }
+
+    /// Returns the standard gamepad button which a controller button is translated as.
+    pub fn get_gamepad_button(&self, button: Button) -> Option<GamepadButton> {
+        self.button_data.gamepads.button(button)
+    }
+
+    /// Returns the id of the glyph which shows a binding, as `GamepadButton::glyph_id` does.
+    /// A raw controller button is shown as the standard gamepad button which the mapping of
+    /// its controller maps it to. Other bindings don't have glyphs.
+    pub fn get_glyph_id(&self, binding: Binding) -> Option<&'static str> {
+        match binding {
+            Binding::Gamepad(button) => Some(button.glyph_id()),
+            Binding::Button(button @ Button::Controller(_)) => self.get_gamepad_button(button).map(|b| b.glyph_id()),
+            _ => None,
+        }
+    }
+
+    /// Returns the standard gamepad axis which a controller axis motion is on.
+    pub fn get_gamepad_axis(&self, args: ControllerAxisArgs) -> Option<GamepadAxis> {
+        self.button_data.gamepads.mapping(args.id).and_then(|m| m.get_axis(args.axis))
+    }
+
+    /// Set whether the `South` and `East` gamepad buttons are swapped, so that the confirm
+    /// and cancel buttons follow the Nintendo convention. Any held button which is no longer
+    /// translated to the action it was pressed as is released.
+    pub fn set_swap_confirm_cancel(&mut self, swap: bool) -> Vec<Translated<A>> {
+        self.button_data.gamepads.swap_confirm_cancel = swap;
+        self.invalidate_held_bindings()
+    }
+
+    /// Returns true if the `South` and `East` gamepad buttons are swapped.
+    pub fn get_swap_confirm_cancel(&self) -> bool {
+        self.button_data.gamepads.swap_confirm_cancel
+    }
+
+    /// Set the calibration of the controller, which corrects the positions of its axes
+    /// before anything else, such as the deadzone of the virtual cursor, sees them, until it
+    /// is disconnected or connected again according to `controller_event`. `None` removes it.
+    pub fn set_calibration(&mut self, controller_id: i32, calibration: Option<DeviceCalibration>) {
+        self.button_data.gamepads.set_calibration(controller_id, calibration);
+    }
+
+    /// Returns the calibration of the controller.
+    pub fn get_calibration(&self, controller_id: i32) -> Option<&DeviceCalibration> {
+        self.button_data.gamepads.calibration(controller_id)
+    }
+
+    /// Set the stored calibrations, which are used for controllers as they are connected,
+    /// according to `controller_event`.
+    pub fn set_calibration_store(&mut self, store: Option<CalibrationStore>) {
+        self.button_data.gamepads.calibration_store = store.map(Arc::new);
+    }
+
+    /// Returns the stored calibrations.
+    pub fn get_calibration_store(&self) -> Option<&CalibrationStore> {
+        self.button_data.gamepads.calibration_store.as_ref().map(|s| &**s)
+    }
+
+    /// Returns the class of device which produced the most recent input that showed which
+    /// device the player is using, according to `translate_stateful`.
+    pub fn get_last_device_class(&self) -> Option<DeviceClass> {
+        self.state.last_device_class()
+    }
+
+    /// Set how far a controller axis must be from the centre for its motion to show that
+    /// the player is using the controller.
+    pub fn set_analog_noise_threshold(&mut self, threshold: f64) {
+        self.button_data.analog_noise_threshold = threshold;
+    }
+
+    /// Returns how far a controller axis must be from the centre to change the last used
+    /// device class.
+    pub fn get_analog_noise_threshold(&self) -> f64 {
+        self.button_data.analog_noise_threshold
+    }
+
+    /// Set the callback which is notified when the translator has to correct for lost or
+    /// unusual input.
+    pub fn set_diagnostics_callback<F: Fn(&Diagnostic) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.button_data.diagnostics = Some(Shared(Arc::new(callback)));
+    }
+
+    /// Set the callback which is notified when the last used device class changes, for
+    /// example to switch the button prompts between keyboard and gamepad glyphs.
+    pub fn set_device_class_callback<F: Fn(DeviceClass) + Send + Sync + 'static>(&mut self, callback: F) {
+        self.button_data.device_class_changed = Some(Shared(Arc::new(callback)));
+    }
+
     /// Re-set the mouse bounds size used for calculating mouse events
     pub fn set_size<Sz: Into<Size>>(&mut self, size: Sz) {
         self.mouse_translator.data.viewport_size = size.into()
@@ -251,12 +1046,44 @@ impl<A: Action, S: BuildHasher + Default> InputTranslator<A, S> {
     }
 }
 
+#[derive(Clone, Debug, PartialEq)]
+struct ButtonTranslationData {
+    suppress_input_after_focus: bool,
+    filter_repeated_presses: bool,
+    max_hold_time: Option<f64>,
+    analog_noise_threshold: f64,
+    gamepads: GamepadLayout,
+    diagnostics: Option<DiagnosticsCallback>,
+    device_class_changed: Option<DeviceClassCallback>
+}
+
+impl ButtonTranslationData {
+    fn new() -> Self {
+        ButtonTranslationData {
+            suppress_input_after_focus: false,
+            filter_repeated_presses: false,
+            max_hold_time: None,
+            analog_noise_threshold: 0.3,
+            gamepads: GamepadLayout::new(),
+            diagnostics: None,
+            device_class_changed: None
+        }
+    }
+}
+
 #[derive(Clone)]
 struct MouseTranslationData {
     x_axis_motion_inverted: bool,
     y_axis_motion_inverted: bool,
     x_axis_scroll_inverted: bool,
     y_axis_scroll_inverted: bool,
+    scroll_step: f64,
+    drag_threshold: Option<f64>,
+    multi_click_interval: f64,
+    multi_click_distance: f64,
+    gestures: GestureSettings,
+    edge_scroll_margin: Option<f64>,
+    synthesize_relative_motion: bool,
     sensitivity: f64,
     viewport_size: Size
 }
@@ -268,6 +1095,13 @@ impl MouseTranslationData {
             y_axis_motion_inverted: false,
             x_axis_scroll_inverted: false,
             y_axis_scroll_inverted: false,
+            scroll_step: 1.0,
+            drag_threshold: None,
+            multi_click_interval: 0.5,
+            multi_click_distance: 4.0,
+            gestures: GestureSettings::new(),
+            edge_scroll_margin: None,
+            synthesize_relative_motion: false,
             sensitivity: 0.0,
             viewport_size: size.into()
         }
@@ -277,11 +1111,18 @@ impl MouseTranslationData {
 impl Debug for MouseTranslationData {
     fn fmt(&self, f: &mut Formatter) -> Result {
         write!(f,
-               "{}, {}, {}, {}, {}, ({}, {})",
+               "{}, {}, {}, {}, {}, {:?}, {}, {}, {:?}, {:?}, {}, {}, ({}, {})",
                self.x_axis_motion_inverted,
                self.y_axis_motion_inverted,
                self.x_axis_scroll_inverted,
                self.y_axis_scroll_inverted,
+               self.scroll_step,
+               self.drag_threshold,
+               self.multi_click_interval,
+               self.multi_click_distance,
+               self.gestures,
+               self.edge_scroll_margin,
+               self.synthesize_relative_motion,
                self.sensitivity,
                self.viewport_size.width,
                self.viewport_size.height)
@@ -294,6 +1135,11 @@ impl PartialEq for MouseTranslationData {
         self.y_axis_motion_inverted == other.y_axis_motion_inverted &&
         self.x_axis_scroll_inverted == other.x_axis_scroll_inverted &&
         self.y_axis_scroll_inverted == other.y_axis_scroll_inverted &&
+        self.scroll_step == other.scroll_step && self.drag_threshold == other.drag_threshold &&
+        self.multi_click_interval == other.multi_click_interval &&
+        self.multi_click_distance == other.multi_click_distance && self.gestures == other.gestures &&
+        self.edge_scroll_margin == other.edge_scroll_margin &&
+        self.synthesize_relative_motion == other.synthesize_relative_motion &&
         self.sensitivity == other.sensitivity && self.viewport_size.width == other.viewport_size.width &&
         self.viewport_size.height == other.viewport_size.height
     }
@@ -330,14 +1176,49 @@ impl MouseTranslator {
             relative => relative
         }
     }
+
+    /// Calculate the edge scroll axis for a translated cursor position. Each component is
+    /// zero unless the cursor is within the margin of an edge, and ramps linearly up to a
+    /// magnitude of one at the edge itself, pointing towards that edge.
+    fn edge_scroll(&self, cursor: [f64; 2]) -> [f64; 2] {
+        let margin = match self.data.edge_scroll_margin {
+            Some(margin) if margin > 0.0 => margin,
+            _ => return [0.0, 0.0],
+        };
+        let Size {width, height} = self.data.viewport_size;
+        let (sw, sh) = (width as f64, height as f64);
+        if cursor[0] < 0.0 || cursor[0] > sw || cursor[1] < 0.0 || cursor[1] > sh {
+            return [0.0, 0.0];
+        }
+
+        let ramp = |pos: f64, size: f64| {
+            let towards_start = ((margin - pos) / margin).max(0.0).min(1.0);
+            let towards_end = ((margin - (size - pos)) / margin).max(0.0).min(1.0);
+            towards_end - towards_start
+        };
+        [ramp(cursor[0], sw), ramp(cursor[1], sh)]
+    }
 }
 
 /// An interface for rebinding keys to actions. This is freely convertable to and
-/// from an InputTranslator.
-#[derive(Clone, Debug, PartialEq)]
+/// from an InputTranslator. The buttons held in the `InputTranslator` are remembered
+/// across the conversion, so that any held action whose binding is changed is released
+/// by the next call to `InputTranslator::translate_stateful` or `InputTranslator::update`.
+/// The held buttons aren't compared for equality.
+#[derive(Clone, Debug)]
 pub struct InputRebind<A: Action, S: BuildHasher = RandomState> {
     keymap: HashMap<A, ButtonTuple, S>,
-    mouse_data: MouseTranslationData
+    regions: Vec<RegionBinding<A>>,
+    mouse_data: MouseTranslationData,
+    button_data: ButtonTranslationData,
+    state: TranslationState<A>
+}
+
+impl<A: Action, S: BuildHasher> PartialEq for InputRebind<A, S> {
+    fn eq(&self, other: &Self) -> bool {
+        self.keymap == other.keymap && self.regions == other.regions && self.mouse_data == other.mouse_data &&
+        self.button_data == other.button_data
+    }
 }
 
 impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
@@ -345,7 +1226,10 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
     pub fn new<Sz: Into<Size>>(size: Sz) -> Self {
         InputRebind {
             keymap: HashMap::<_, _, S>::default(),
-            mouse_data: MouseTranslationData::new(size)
+            regions: vec![],
+            mouse_data: MouseTranslationData::new(size),
+            button_data: ButtonTranslationData::new(),
+            state: TranslationState::new()
         }
     }
 
@@ -358,9 +1242,9 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
 
     /// Insert an Action into this InputRebind, and assign it to the ButtonTuple.
     /// If the Action is already in the InputRebind, the old ButtonTuple will be
-    /// returned.
-    pub fn insert_action_with_buttons(&mut self, action: A, buttons: ButtonTuple) -> Option<ButtonTuple> {
-        self.keymap.insert(action, buttons)
+    /// returned. This doesn't check `ActionInfo::is_rebindable`.
+    pub fn insert_action_with_buttons<T: Into<ButtonTuple>>(&mut self, action: A, buttons: T) -> Option<ButtonTuple> {
+        self.keymap.insert(action, buttons.into())
     }
 
     /// Return a reference to the current ButtonTuple stored for an action. If the action
@@ -370,11 +1254,27 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
     }
 
     /// Returns a mutable reference to the current ButtonTuple stored for an action. If the
-    /// action is not stored in this InputRebind, then `None` will be returned.
+    /// action is not stored in this InputRebind, then `None` will be returned. Changes made
+    /// through it don't check `ActionInfo::is_rebindable`.
     pub fn get_bindings_mut(&mut self, action: &mut A) -> Option<&mut ButtonTuple> {
         self.keymap.get_mut(action)
     }
 
+    /// Add a region binding, which binds a click inside a region of the window to an action.
+    pub fn insert_region_mapping(&mut self, binding: RegionBinding<A>) {
+        self.regions.push(binding);
+    }
+
+    /// Remove every region binding for the action.
+    pub fn remove_region_mappings(&mut self, action: A) {
+        self.regions.retain(|r| r.action != action);
+    }
+
+    /// Returns the region bindings, in the order they were added.
+    pub fn get_region_mappings(&self) -> &[RegionBinding<A>] {
+        &self.regions
+    }
+
     /// Returns whether mouse scroll along the x axis is inverted.
     pub fn get_x_scroll_inverted(&self) -> bool {
         self.mouse_data.x_axis_scroll_inverted
@@ -395,6 +1295,65 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
         self.mouse_data.y_axis_scroll_inverted = invert_y_scroll;
     }
 
+    /// Returns the scroll distance which makes up one step of a scroll binding.
+    pub fn get_scroll_step(&self) -> f64 {
+        self.mouse_data.scroll_step
+    }
+
+    /// Set the scroll distance which makes up one step of a scroll binding. Smaller
+    /// scroll deltas are accumulated until a whole step has been scrolled.
+    pub fn set_scroll_step(&mut self, scroll_step: f64) {
+        self.mouse_data.scroll_step = scroll_step;
+    }
+
+    /// Returns the distance in pixels which the cursor must move while a mouse button is
+    /// held for a drag to start, or `None` if drags are not detected.
+    pub fn get_drag_threshold(&self) -> Option<f64> {
+        self.mouse_data.drag_threshold
+    }
+
+    /// Set the distance in pixels which the cursor must move while a mouse button is held
+    /// for a drag to start. `None` disables drag detection.
+    pub fn set_drag_threshold(&mut self, drag_threshold: Option<f64>) {
+        self.mouse_data.drag_threshold = drag_threshold;
+    }
+
+    /// Returns the maximum time in seconds between consecutive clicks of a double or
+    /// triple click.
+    pub fn get_multi_click_interval(&self) -> f64 {
+        self.mouse_data.multi_click_interval
+    }
+
+    /// Set the maximum time in seconds between consecutive clicks of a double or triple
+    /// click, as timed by `InputTranslator::update`.
+    pub fn set_multi_click_interval(&mut self, interval: f64) {
+        self.mouse_data.multi_click_interval = interval;
+    }
+
+    /// Returns the maximum distance in pixels which the cursor may move between
+    /// consecutive clicks of a double or triple click.
+    pub fn get_multi_click_distance(&self) -> f64 {
+        self.mouse_data.multi_click_distance
+    }
+
+    /// Set the maximum distance in pixels which the cursor may move between consecutive
+    /// clicks of a double or triple click.
+    pub fn set_multi_click_distance(&mut self, distance: f64) {
+        self.mouse_data.multi_click_distance = distance;
+    }
+
+    /// Returns the distance in pixels from the edge of the window within which the cursor
+    /// causes edge scrolling, or `None` if edge scrolling is disabled.
+    pub fn get_edge_scroll_margin(&self) -> Option<f64> {
+        self.mouse_data.edge_scroll_margin
+    }
+
+    /// Set the distance in pixels from the edge of the window within which the cursor
+    /// causes edge scrolling. `None` disables edge scrolling.
+    pub fn set_edge_scroll_margin(&mut self, margin: Option<f64>) {
+        self.mouse_data.edge_scroll_margin = margin;
+    }
+
     /// Returns whether mouse motion along the x axis is inverted.
     pub fn get_x_motion_inverted(&self) -> bool {
         self.mouse_data.x_axis_motion_inverted
@@ -425,6 +1384,44 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
         self.mouse_data.viewport_size = viewport_size;
     }
 
+    /// Returns whether the first button press after the window regains focus is ignored.
+    pub fn get_suppress_input_after_focus(&self) -> bool {
+        self.button_data.suppress_input_after_focus
+    }
+
+    /// Set whether the first button press after the window regains focus is ignored.
+    pub fn set_suppress_input_after_focus(&mut self, suppress: bool) {
+        self.button_data.suppress_input_after_focus = suppress;
+    }
+
+    /// Returns true if the `South` and `East` gamepad buttons are swapped.
+    pub fn get_swap_confirm_cancel(&self) -> bool {
+        self.button_data.gamepads.swap_confirm_cancel
+    }
+
+    /// Set whether the `South` and `East` gamepad buttons are swapped, so that the confirm
+    /// and cancel buttons follow the Nintendo convention.
+    pub fn set_swap_confirm_cancel(&mut self, swap: bool) {
+        self.button_data.gamepads.swap_confirm_cancel = swap;
+    }
+
+    /// Returns the bindings and mouse settings, so that they can be saved.
+    pub fn get_profile(&self) -> Profile<A> {
+        let mut keymap = self.keymap.iter().map(|(&a, &bt)| (a, bt)).collect_vec();
+        keymap.sort_by(|a, b| a.0.cmp(&b.0));
+        Profile { keymap: keymap, mouse: profile::mouse_settings(&self.mouse_data) }
+    }
+
+    /// Replace the bindings and mouse settings with those of the profile, including the
+    /// bindings of actions which `ActionInfo::is_rebindable` says can't be rebound. Nothing
+    /// is changed if the profile binds a binding to more than one action.
+    pub fn set_profile(&mut self, profile: &Profile<A>) -> ::std::result::Result<(), ProfileError<A>> {
+        profile.check()?;
+        self.keymap = profile.keymap.iter().cloned().collect();
+        profile::apply_mouse_settings(&mut self.mouse_data, &profile.mouse);
+        Ok(())
+    }
+
     /// Convert the `InputRebind` into an `InputTranslator`. Consumes the
     /// `InputRebind`.
     pub fn into_translator(self) -> InputTranslator<A, S> {
@@ -432,10 +1429,74 @@ impl<A: Action, S: BuildHasher + Default> InputRebind<A, S> {
     }
 }
 
+impl<A: NamedAction, S: BuildHasher + Default> InputRebind<A, S> {
+    /// Returns the buttons bound to every action, in the order the actions are declared,
+    /// including actions which haven't been inserted, which have no buttons.
+    pub fn get_all_bindings(&self) -> Vec<(A, ButtonTuple)> {
+        A::all().iter().map(|&a| (a, self.keymap.get(&a).cloned().unwrap_or_default())).collect()
+    }
+
+    /// Returns the actions which can't be triggered, because they aren't bound to any
+    /// button or region.
+    pub fn get_unbound_actions(&self) -> Vec<A> {
+        A::all()
+            .iter()
+            .cloned()
+            .filter(|a| self.keymap.get(a).map_or(true, |bt| bt.into_iter().all(|b| b.is_none())))
+            .filter(|&a| self.regions.iter().all(|r| r.action != a))
+            .collect()
+    }
+}
+
+impl<A: ActionInfo, S: BuildHasher + Default> InputRebind<A, S> {
+    /// Bind the action to the buttons, replacing its previous buttons, which are returned.
+    /// This is refused if the action can't be rebound, or if one of the buttons is bound
+    /// to an action which can't be rebound. Buttons which were bound to other actions are
+    /// removed from them.
+    pub fn rebind_action<T>(&mut self, action: A, buttons: T) -> ::std::result::Result<ButtonTuple, RebindError<A>>
+        where T: Into<ButtonTuple>
+    {
+        let buttons = buttons.into();
+        if !action.is_rebindable() {
+            return Err(RebindError::Locked(action));
+        }
+        for b in buttons.into_iter().filter_map(|b| b) {
+            let locked = self.keymap.iter().find(|&(&a, bt)| a != action && !a.is_rebindable() && bt.contains(b));
+            if let Some((&a, _)) = locked {
+                return Err(RebindError::Conflict(a, b));
+            }
+        }
+
+        let unbound = |b: Option<Binding>| b.and_then(|b| if buttons.contains(b) { None } else { Some(b) });
+        for bt in self.keymap.values_mut() {
+            *bt = ButtonTuple(unbound(bt.0), unbound(bt.1), unbound(bt.2));
+        }
+        Ok(self.keymap.insert(action, buttons).unwrap_or_default())
+    }
+}
+
+impl<A: ActionInfo + NamedAction, S: BuildHasher + Default> InputRebind<A, S> {
+    /// Returns the buttons bound to every action, grouped by category. The categories are
+    /// in the order of their first action, and the actions in each category are in the
+    /// order they are declared.
+    pub fn get_bindings_by_category(&self) -> Vec<(Option<&'static str>, Vec<(A, ButtonTuple)>)> {
+        let mut categories: Vec<(Option<&'static str>, Vec<(A, ButtonTuple)>)> = vec![];
+        for (action, bt) in self.get_all_bindings() {
+            match categories.iter().position(|&(c, _)| c == action.category()) {
+                Some(i) => categories[i].1.push((action, bt)),
+                None => categories.push((action.category(), vec![(action, bt)])),
+            }
+        }
+        categories
+    }
+}
+
 impl<A: Action, S: BuildHasher + Default> Into<InputTranslator<A, S>> for InputRebind<A, S> {
     fn into(self) -> InputTranslator<A, S> {
         let mut input_translator = InputTranslator::new(self.mouse_data.viewport_size);
         input_translator.mouse_translator.data = self.mouse_data;
+        input_translator.button_data = self.button_data;
+        input_translator.regions = self.regions;
         let key_vec = self.keymap
                           .values()
                           .flat_map(|bt| bt.into_iter().filter_map(|x| x))
@@ -450,6 +1511,9 @@ impl<A: Action, S: BuildHasher + Default> Into<InputTranslator<A, S>> for InputR
             }
         }
 
+        input_translator.state = self.state;
+        input_translator.invalidate_held_bindings_later();
+
         input_translator
     }
 }
@@ -459,16 +1523,19 @@ impl<A: Action, S: BuildHasher + Default> Into<InputRebind<A, S>> for InputTrans
         let mut input_rebind = InputRebind::new(self.mouse_translator.data.viewport_size);
 
         input_rebind.mouse_data = self.mouse_translator.data;
+        input_rebind.button_data = self.button_data;
+        input_rebind.state = self.state;
+        input_rebind.regions = self.regions;
         input_rebind.keymap = to_act_bt_hashmap(self.keymap.iter().map(|(b, a)| (*b, *a)));
 
         input_rebind
     }
 }
 
-/// Utility function to convert an iterator of (Button, Action) to a
+/// Utility function to convert an iterator of (Binding, Action) to a
 /// `HashMap<Action, ButtonTuple>`.
 fn to_act_bt_hashmap<I, A, S>(iter: I) -> HashMap<A, ButtonTuple, S>
-    where I: Iterator<Item = (Button, A)>,
+    where I: Iterator<Item = (Binding, A)>,
           A: Action,
           S: BuildHasher + Default {
     iter.map(|(b, a)| (a, vec![Some(b)]))
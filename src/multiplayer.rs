@@ -0,0 +1,434 @@
+use {Action, ControllerEvent, ControllerGuid, InputTranslator, Translated};
+use input::{Button, ControllerAxisArgs, ControllerButton, Input, Key, Motion};
+use std::collections::hash_map::RandomState;
+use std::hash::BuildHasher;
+
+/// A source of input which can be owned by a player.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum Device {
+    /// The whole keyboard.
+    Keyboard,
+    /// One half of the keyboard, so that two players can share it.
+    KeyboardHalf(KeyboardHalf),
+    /// The mouse, including its buttons, motion and scrolling.
+    Mouse,
+    /// The controller with the given id.
+    Controller(i32)
+}
+
+/// One half of a keyboard. The left half holds the keys which are normally pressed with the
+/// left hand on a QWERTY layout, from `Escape` and `F1`-`F6` down to `Space`, `LCtrl`,
+/// `LGui` and `LAlt`, with `T`, `G` and `B` as its rightmost letters. Every other key,
+/// including the arrow keys and the number pad, is in the right half.
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum KeyboardHalf {
+    /// The left half.
+    Left,
+    /// The right half.
+    Right
+}
+
+impl KeyboardHalf {
+    /// Returns the half of the keyboard which the key is in.
+    pub fn of_key(key: Key) -> Self {
+        match key {
+            Key::Escape | Key::Backquote | Key::D1 | Key::D2 | Key::D3 | Key::D4 | Key::D5 | Key::Tab | Key::Q |
+            Key::W | Key::E | Key::R | Key::T | Key::CapsLock | Key::A | Key::S | Key::D | Key::F | Key::G |
+            Key::LShift | Key::Z | Key::X | Key::C | Key::V | Key::B | Key::LCtrl | Key::LGui | Key::LAlt |
+            Key::Space | Key::F1 | Key::F2 | Key::F3 | Key::F4 | Key::F5 | Key::F6 => KeyboardHalf::Left,
+            _ => KeyboardHalf::Right,
+        }
+    }
+
+    fn other(self) -> Self {
+        match self {
+            KeyboardHalf::Left => KeyboardHalf::Right,
+            KeyboardHalf::Right => KeyboardHalf::Left,
+        }
+    }
+}
+
+impl Device {
+    /// Returns the device which produced the button. Keys are reported as being from a
+    /// `KeyboardHalf`, which is contained by `Keyboard`.
+    pub fn of_button(button: Button) -> Self {
+        match button {
+            Button::Keyboard(key) => Device::KeyboardHalf(KeyboardHalf::of_key(key)),
+            Button::Mouse(_) => Device::Mouse,
+            Button::Controller(b) => Device::Controller(b.id),
+        }
+    }
+
+    /// Returns the device which produced the input, or `None` if the input is not from a
+    /// particular device, such as a change of focus.
+    pub fn of_input(input: &Input) -> Option<Self> {
+        match input {
+            &Input::Press(button) | &Input::Release(button) => Some(Device::of_button(button)),
+            &Input::Move(Motion::ControllerAxis(args)) => Some(Device::Controller(args.id)),
+            &Input::Move(_) => Some(Device::Mouse),
+            _ => None,
+        }
+    }
+
+    /// Returns true if input from the other device is also input from this one. A device
+    /// contains itself, and `Keyboard` contains both of its halves.
+    pub fn contains(&self, other: Device) -> bool {
+        match (*self, other) {
+            (Device::Keyboard, Device::KeyboardHalf(_)) => true,
+            (a, b) => a == b,
+        }
+    }
+
+    /// Returns true if the button is on this device.
+    pub fn has_button(&self, button: Button) -> bool {
+        self.contains(Device::of_button(button))
+    }
+}
+
+/// An event produced by a `PlayerRouter`.
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum Routed<A: Action> {
+    /// An event translated by the bindings of the player with the given index.
+    Player(usize, Translated<A>),
+    /// A device pressed a join button, and was given to a new player with the given index.
+    Joined(usize, Device)
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Player<A: Action, S: BuildHasher> {
+    translator: InputTranslator<A, S>,
+    devices: Vec<Device>,
+    local_ids: Vec<(i32, i32)>
+}
+
+impl<A: Action, S: BuildHasher + Default> Player<A, S> {
+    fn new(translator: InputTranslator<A, S>) -> Self {
+        Player { translator: translator, devices: vec![], local_ids: vec![] }
+    }
+
+    /// Give the device to the player. A controller is given the lowest local id which
+    /// none of the player's other controllers has.
+    fn give(&mut self, device: Device) {
+        if let Device::Controller(id) = device {
+            let local = (0..).find(|l| self.local_ids.iter().all(|&(_, o)| o != *l)).unwrap_or(0);
+            self.local_ids.push((id, local));
+        }
+        self.devices.push(device);
+    }
+
+    /// Returns the id which the player's translator knows the controller by.
+    fn local_id(&self, id: i32) -> i32 {
+        self.local_ids.iter().find(|&&(i, _)| i == id).map_or(id, |&(_, l)| l)
+    }
+
+    fn local_input(&self, input: &Input) -> Input {
+        let local_button = |button| match button {
+            Button::Controller(b) => Button::Controller(ControllerButton { id: self.local_id(b.id), button: b.button }),
+            button => button,
+        };
+        match input {
+            &Input::Press(button) => Input::Press(local_button(button)),
+            &Input::Release(button) => Input::Release(local_button(button)),
+            &Input::Move(Motion::ControllerAxis(args)) => {
+                Input::Move(Motion::ControllerAxis(ControllerAxisArgs {
+                    id: self.local_id(args.id),
+                    axis: args.axis,
+                    position: args.position
+                }))
+            }
+            input => input.clone(),
+        }
+    }
+}
+
+/// Routes input to several local players, each of whom owns some devices and has their own
+/// `InputTranslator`, and so their own bindings and held buttons. Input from a device goes
+/// to the player who owns it, and input which is not from a device, such as a change of
+/// focus, goes to every player. Every player's translator sees their first controller as the
+/// controller with id 0, so that players can share bindings whichever controller they use.
+/// A player who is given several controllers sees each of them by its own local id, which is
+/// the lowest id that none of their other controllers has, so their second controller has
+/// id 1. Everything a translator keeps for a controller, such as its gamepad mapping, its
+/// calibration and its entry in `InputTranslator::get_controllers`, is kept under its local
+/// id. A player's translator receives a `ControllerEvent::Connected` for the local id
+/// whenever they are given a connected controller, and a `ControllerEvent::Disconnected`
+/// whenever it is taken away from them, so that the mapping and calibration for its GUID are
+/// applied, and their other controllers are left alone.
+///
+/// Input from a device which nobody owns is ignored, unless it is the press of a join
+/// button, in which case a new player is added with a copy of the template translator and
+/// given the device. This gives a "press start to join" flow.
+///
+/// When a controller which a player owns is disconnected, the player keeps their place, and
+/// is given back the next controller with the same GUID to be connected.
+#[derive(Clone, Debug, PartialEq)]
+pub struct PlayerRouter<A: Action, S: BuildHasher = RandomState> {
+    template: InputTranslator<A, S>,
+    players: Vec<Player<A, S>>,
+    join_buttons: Vec<Button>,
+    controllers: Vec<(i32, ControllerGuid)>,
+    detached: Vec<(usize, ControllerGuid)>,
+    join_enabled: bool,
+    split_keyboard: bool,
+    max_players: Option<usize>
+}
+
+impl<A: Action, S: BuildHasher + Clone + Default> PlayerRouter<A, S> {
+    /// Creates a router without any players. Players who join are given a copy of the
+    /// template translator.
+    pub fn new(template: InputTranslator<A, S>) -> Self {
+        PlayerRouter {
+            template: template,
+            players: vec![],
+            join_buttons: vec![],
+            controllers: vec![],
+            detached: vec![],
+            join_enabled: true,
+            split_keyboard: false,
+            max_players: None
+        }
+    }
+
+    /// Add a player with their own translator, returning the index of the player.
+    pub fn add_player(&mut self, translator: InputTranslator<A, S>) -> usize {
+        self.players.push(Player::new(translator));
+        self.players.len() - 1
+    }
+
+    /// Returns the number of players.
+    pub fn num_players(&self) -> usize {
+        self.players.len()
+    }
+
+    /// Returns the translator of the player.
+    pub fn get_player(&self, player: usize) -> Option<&InputTranslator<A, S>> {
+        self.players.get(player).map(|p| &p.translator)
+    }
+
+    /// Returns the translator of the player mutably, so that their bindings can be changed.
+    pub fn get_player_mut(&mut self, player: usize) -> Option<&mut InputTranslator<A, S>> {
+        self.players.get_mut(player).map(|p| &mut p.translator)
+    }
+
+    /// Returns the devices owned by the player.
+    pub fn get_devices(&self, player: usize) -> &[Device] {
+        self.players.get(player).map(|p| &p.devices[..]).unwrap_or(&[])
+    }
+
+    /// Returns the player who owns the device, or who owns a device containing it.
+    pub fn get_owner(&self, device: Device) -> Option<usize> {
+        self.players.iter().position(|p| p.devices.iter().any(|d| d.contains(device)))
+    }
+
+    /// Give the device to the player, taking it away from anybody else who owns it or part
+    /// of it. Buttons of the device which are held by its previous owner are released. If
+    /// the device is a controller which is connected, the player's translator is told that
+    /// it has been connected. If the player doesn't exist, the device is only taken away.
+    pub fn assign_device(&mut self, player: usize, device: Device) -> Vec<Routed<A>> {
+        let mut routed = self.unassign_device(device);
+        if let Some(p) = self.players.get_mut(player) {
+            p.give(device);
+        } else {
+            return routed;
+        }
+        routed.extend(self.connect_device(player, device));
+        routed
+    }
+
+    /// Take the device, and any part of it, away from whoever owns it. Buttons of the device
+    /// which are held are released, and a controller is disconnected from the translator
+    /// of its owner.
+    pub fn unassign_device(&mut self, device: Device) -> Vec<Routed<A>> {
+        let mut routed = vec![];
+        for (i, p) in self.players.iter_mut().enumerate() {
+            let owned = p.devices.len();
+            p.devices.retain(|d| !device.contains(*d));
+            if let (Device::KeyboardHalf(half), Some(pos)) =
+                   (device, p.devices.iter().position(|&d| d == Device::Keyboard)) {
+                p.devices[pos] = Device::KeyboardHalf(half.other());
+            } else if p.devices.len() == owned {
+                continue;
+            }
+            let translated = match device {
+                Device::Controller(id) => {
+                    let local = p.local_id(id);
+                    p.local_ids.retain(|&(i, _)| i != id);
+                    p.translator.controller_event(ControllerEvent::Disconnected(local))
+                }
+                device => p.translator.release_buttons(|b| device.has_button(b)),
+            };
+            routed.extend(translated.into_iter().map(|t| Routed::Player(i, t)));
+        }
+        routed
+    }
+
+    /// Tell the translator of the player that the device has been connected, if it is a
+    /// controller whose GUID is known.
+    fn connect_device(&mut self, player: usize, device: Device) -> Vec<Routed<A>> {
+        let id = match device {
+            Device::Controller(id) => id,
+            _ => return vec![],
+        };
+        match self.controllers.iter().find(|&&(i, _)| i == id) {
+            Some(&(_, guid)) => {
+                let local = ControllerEvent::Connected(self.players[player].local_id(id), guid);
+                self.route_to(player, |t| t.controller_event(local))
+            }
+            None => vec![],
+        }
+    }
+
+    /// Track a controller being connected or disconnected. When a player's controller is
+    /// disconnected, it is passed to their `InputTranslator::controller_event`, which
+    /// releases its actions and produces a `ControllerDisconnected`. When a controller with
+    /// the same GUID is connected, it is given to the player who lost it, and they receive a
+    /// `ControllerConnected`. If several players are waiting for a controller with the same
+    /// GUID, the one who lost theirs first gets it. A controller which already has an owner
+    /// is passed to the owner's translator.
+    pub fn controller_event(&mut self, event: ControllerEvent) -> Vec<Routed<A>> {
+        match event {
+            ControllerEvent::Connected(id, guid) => {
+                self.controllers.retain(|&(i, _)| i != id);
+                self.controllers.push((id, guid));
+                let device = Device::Controller(id);
+                if let Some(player) = self.get_owner(device) {
+                    return self.connect_device(player, device);
+                }
+                match self.detached.iter().position(|&(_, g)| g == guid) {
+                    Some(i) => {
+                        let (player, _) = self.detached.remove(i);
+                        self.assign_device(player, device)
+                    }
+                    None => vec![],
+                }
+            }
+            ControllerEvent::Disconnected(id) => {
+                let guid = match self.controllers.iter().position(|&(i, _)| i == id) {
+                    Some(i) => Some(self.controllers.remove(i).1),
+                    None => None,
+                };
+                let device = Device::Controller(id);
+                match self.get_owner(device) {
+                    Some(player) => {
+                        if let Some(guid) = guid {
+                            self.detached.push((player, guid));
+                        }
+                        self.unassign_device(device)
+                    }
+                    None => vec![],
+                }
+            }
+        }
+    }
+
+    /// Returns the players whose controller has been disconnected, and who are waiting for
+    /// it to be reconnected, in the order that they lost it.
+    pub fn get_detached_players(&self) -> Vec<usize> {
+        self.detached.iter().map(|&(p, _)| p).collect()
+    }
+
+    /// Add a button which a device nobody owns can press to join as a new player. The id of
+    /// a controller button is ignored, so that any controller can join with it.
+    pub fn add_join_button(&mut self, button: Button) {
+        if !self.join_buttons.contains(&button) {
+            self.join_buttons.push(button);
+        }
+    }
+
+    /// Returns the join buttons.
+    pub fn get_join_buttons(&self) -> &[Button] {
+        &self.join_buttons
+    }
+
+    /// Set whether new players can join, for example to stop joining once a game has
+    /// started.
+    pub fn set_join_enabled(&mut self, enabled: bool) {
+        self.join_enabled = enabled;
+    }
+
+    /// Returns true if new players can join.
+    pub fn get_join_enabled(&self) -> bool {
+        self.join_enabled
+    }
+
+    /// Set whether a keyboard joins as only the half of it which the join button is on,
+    /// so that a second player can join from the other half.
+    pub fn set_split_keyboard(&mut self, split: bool) {
+        self.split_keyboard = split;
+    }
+
+    /// Returns true if a keyboard joins as only half of it.
+    pub fn get_split_keyboard(&self) -> bool {
+        self.split_keyboard
+    }
+
+    /// Set the maximum number of players, after which nobody else can join. `None` removes
+    /// the limit.
+    pub fn set_max_players(&mut self, max_players: Option<usize>) {
+        self.max_players = max_players;
+    }
+
+    /// Returns the maximum number of players.
+    pub fn get_max_players(&self) -> Option<usize> {
+        self.max_players
+    }
+
+    /// Route the input to the player who owns its device, translating it with
+    /// `InputTranslator::translate_stateful`.
+    pub fn translate(&mut self, input: &Input) -> Vec<Routed<A>> {
+        let device = match Device::of_input(input) {
+            Some(device) => device,
+            None => {
+                let mut routed = vec![];
+                for (i, p) in self.players.iter_mut().enumerate() {
+                    routed.extend(p.translator.translate_stateful(input).into_iter().map(|t| Routed::Player(i, t)));
+                }
+                return routed;
+            }
+        };
+
+        match (self.get_owner(device), input) {
+            (Some(i), _) => {
+                let local = self.players[i].local_input(input);
+                self.route_to(i, |t| t.translate_stateful(&local))
+            }
+            (None, &Input::Press(button)) if self.can_join(button) => {
+                let device = match device {
+                    Device::KeyboardHalf(_) if !self.split_keyboard => Device::Keyboard,
+                    device => device,
+                };
+                let player = self.add_player(self.template.clone());
+                self.players[player].give(device);
+                let mut routed = vec![Routed::Joined(player, device)];
+                routed.extend(self.connect_device(player, device));
+                routed
+            }
+            _ => vec![],
+        }
+    }
+
+    /// Advance the time of every player by `dt` seconds, with `InputTranslator::update`.
+    pub fn update(&mut self, dt: f64) -> Vec<Routed<A>> {
+        let mut routed = vec![];
+        for (i, p) in self.players.iter_mut().enumerate() {
+            routed.extend(p.translator.update(dt).into_iter().map(|t| Routed::Player(i, t)));
+        }
+        routed
+    }
+
+    fn route_to<F>(&mut self, player: usize, translate: F) -> Vec<Routed<A>>
+        where F: FnOnce(&mut InputTranslator<A, S>) -> Vec<Translated<A>>
+    {
+        translate(&mut self.players[player].translator).into_iter().map(|t| Routed::Player(player, t)).collect()
+    }
+
+    fn can_join(&self, button: Button) -> bool {
+        let is_join_button = self.join_buttons.iter().any(|&b| {
+            match (b, button) {
+                (Button::Controller(a), Button::Controller(b)) => a.button == b.button,
+                (a, b) => a == b,
+            }
+        });
+        self.join_enabled && is_join_button && self.max_players.map_or(true, |max| self.players.len() < max)
+    }
+}
@@ -0,0 +1,257 @@
+use {Binding, Chord, Gesture, GestureDirection, Sequence};
+use callback::Shared;
+use input::{Button, Key, MouseButton};
+use std::collections::HashMap;
+use std::hash::BuildHasher;
+use std::sync::Arc;
+
+/// Whether a name is written in full, such as "Left Mouse Button", or abbreviated to fit
+/// in a small space, such as "LMB".
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq)]
+pub enum NameForm {
+    /// The abbreviated name.
+    Short,
+
+    /// The full name.
+    Long
+}
+
+/// The translated names which `ButtonNames` shows, looked up by id.
+///
+/// The id of a binding is its written name in lower case, with keys prefixed by `key.`,
+/// such as `key.space`, `mouse.left`, `scroll.up` or `gamepad.south`. The short form of a
+/// name is looked up by the id followed by `.short`, and the long form is used if the
+/// table doesn't have it. The other ids are:
+///
+/// * `controller.button`, with `{pad}` and `{button}` replaced by the numbers of a raw
+///   controller button.
+/// * `double_click` and `triple_click`, with `{button}` replaced by the mouse button.
+/// * `gesture.stroke` with `{direction}`, and `gesture.strokes` with `{first}` and `{second}`,
+///   replaced by the names of the directions, which are `direction.up` and so on.
+/// * `modifier.ctrl`, `modifier.shift`, `modifier.alt` and `modifier.gui`.
+/// * `chord.separator` and `sequence.separator`, which are put between the parts of a chord
+///   and the chords of a sequence.
+pub trait StringTable {
+    /// Returns the text with the id, or `None` if the table doesn't have it.
+    fn get(&self, id: &str) -> Option<String>;
+}
+
+impl<S: BuildHasher> StringTable for HashMap<String, String, S> {
+    fn get(&self, id: &str) -> Option<String> {
+        HashMap::get(self, id).cloned()
+    }
+}
+
+/// The English names, which are shown when the string table doesn't have a name.
+const ENGLISH: &'static [(&'static str, &'static str, &'static str)] = &[
+    ("key.return", "Enter", "Enter"),
+    ("key.escape", "Esc", "Escape"),
+    ("key.backspace", "Bksp", "Backspace"),
+    ("key.space", "Space", "Space Bar"),
+    ("key.delete", "Del", "Delete"),
+    ("key.insert", "Ins", "Insert"),
+    ("key.pageup", "PgUp", "Page Up"),
+    ("key.pagedown", "PgDn", "Page Down"),
+    ("key.up", "Up", "Up Arrow"),
+    ("key.down", "Down", "Down Arrow"),
+    ("key.left", "Left", "Left Arrow"),
+    ("key.right", "Right", "Right Arrow"),
+    ("key.capslock", "Caps", "Caps Lock"),
+    ("key.lctrl", "LCtrl", "Left Ctrl"),
+    ("key.rctrl", "RCtrl", "Right Ctrl"),
+    ("key.lshift", "LShift", "Left Shift"),
+    ("key.rshift", "RShift", "Right Shift"),
+    ("key.lalt", "LAlt", "Left Alt"),
+    ("key.ralt", "RAlt", "Right Alt"),
+    ("key.lgui", "LGui", "Left GUI"),
+    ("key.rgui", "RGui", "Right GUI"),
+    ("mouse.left", "LMB", "Left Mouse Button"),
+    ("mouse.right", "RMB", "Right Mouse Button"),
+    ("mouse.middle", "MMB", "Middle Mouse Button"),
+    ("mouse.x1", "M4", "Mouse Button 4"),
+    ("mouse.x2", "M5", "Mouse Button 5"),
+    ("mouse.button6", "M6", "Mouse Button 6"),
+    ("mouse.button7", "M7", "Mouse Button 7"),
+    ("mouse.button8", "M8", "Mouse Button 8"),
+    ("controller.button", "P{pad}B{button}", "Controller {pad} Button {button}"),
+    ("scroll.up", "Wheel Up", "Mouse Wheel Up"),
+    ("scroll.down", "Wheel Down", "Mouse Wheel Down"),
+    ("scroll.left", "Wheel Left", "Mouse Wheel Left"),
+    ("scroll.right", "Wheel Right", "Mouse Wheel Right"),
+    ("double_click", "2x {button}", "Double-Click {button}"),
+    ("triple_click", "3x {button}", "Triple-Click {button}"),
+    ("direction.up", "Up", "Up"),
+    ("direction.down", "Down", "Down"),
+    ("direction.left", "Left", "Left"),
+    ("direction.right", "Right", "Right"),
+    ("gesture.stroke", "{direction}", "Gesture {direction}"),
+    ("gesture.strokes", "{first} {second}", "Gesture {first} then {second}"),
+    ("gesture.clockwise", "CW", "Clockwise Circle"),
+    ("gesture.anticlockwise", "ACW", "Anticlockwise Circle"),
+    ("gamepad.south", "South", "South Face Button"),
+    ("gamepad.east", "East", "East Face Button"),
+    ("gamepad.west", "West", "West Face Button"),
+    ("gamepad.north", "North", "North Face Button"),
+    ("gamepad.leftshoulder", "L Shoulder", "Left Shoulder"),
+    ("gamepad.rightshoulder", "R Shoulder", "Right Shoulder"),
+    ("gamepad.lefttrigger", "L Trigger", "Left Trigger"),
+    ("gamepad.righttrigger", "R Trigger", "Right Trigger"),
+    ("gamepad.leftstick", "L Stick", "Left Stick Button"),
+    ("gamepad.rightstick", "R Stick", "Right Stick Button"),
+    ("gamepad.dpadup", "D-Pad Up", "D-Pad Up"),
+    ("gamepad.dpaddown", "D-Pad Down", "D-Pad Down"),
+    ("gamepad.dpadleft", "D-Pad Left", "D-Pad Left"),
+    ("gamepad.dpadright", "D-Pad Right", "D-Pad Right"),
+    ("gamepad.start", "Start", "Start"),
+    ("gamepad.back", "Back", "Back"),
+    ("gamepad.guide", "Guide", "Guide Button"),
+    ("modifier.ctrl", "Ctrl", "Ctrl"),
+    ("modifier.shift", "Shift", "Shift"),
+    ("modifier.alt", "Alt", "Alt"),
+    ("modifier.gui", "Gui", "GUI"),
+    ("chord.separator", "+", " + "),
+    ("sequence.separator", " ", ", ")
+];
+
+type Table = Shared<dyn StringTable + Send + Sync>;
+
+/// A string table without any names, so that every name is shown in English.
+struct English;
+
+impl StringTable for English {
+    fn get(&self, _: &str) -> Option<String> {
+        None
+    }
+}
+
+/// Names buttons, chords and sequences for showing to the player, such as in an options
+/// menu, in the language of a string table. Names which the table doesn't have are shown
+/// in English.
+#[derive(Clone, Debug)]
+pub struct ButtonNames {
+    table: Table
+}
+
+impl ButtonNames {
+    /// Creates a `ButtonNames` which shows the English names.
+    pub fn new() -> Self {
+        ButtonNames { table: Shared(Arc::new(English)) }
+    }
+
+    /// Set the string table which the names are looked up in.
+    pub fn string_table<T: StringTable + Send + Sync + 'static>(mut self, table: T) -> Self {
+        self.table = Shared(Arc::new(table));
+        self
+    }
+
+    /// Returns the name of a button.
+    pub fn button_name(&self, button: Button, form: NameForm) -> String {
+        match button {
+            Button::Keyboard(key) => self.key_name(key, form),
+            Button::Mouse(button) => self.mouse_name(button, form),
+            Button::Controller(button) => {
+                self.text("controller.button", form)
+                    .unwrap_or_default()
+                    .replace("{pad}", &button.id.to_string())
+                    .replace("{button}", &button.button.to_string())
+            }
+        }
+    }
+
+    /// Returns the name of a binding.
+    pub fn binding_name(&self, binding: Binding, form: NameForm) -> String {
+        match binding {
+            Binding::Button(button) => self.button_name(button, form),
+            Binding::DoubleClick(button) => {
+                self.text("double_click", form).unwrap_or_default().replace("{button}", &self.mouse_name(button, form))
+            }
+            Binding::TripleClick(button) => {
+                self.text("triple_click", form).unwrap_or_default().replace("{button}", &self.mouse_name(button, form))
+            }
+            Binding::Gesture(Gesture::Stroke(direction)) => {
+                let direction = self.direction_name(direction, form);
+                self.text("gesture.stroke", form).unwrap_or_default().replace("{direction}", &direction)
+            }
+            Binding::Gesture(Gesture::Strokes(first, second)) => {
+                self.text("gesture.strokes", form)
+                    .unwrap_or_default()
+                    .replace("{first}", &self.direction_name(first, form))
+                    .replace("{second}", &self.direction_name(second, form))
+            }
+            Binding::Chord(modifiers, button) => {
+                self.chord_name(&Chord { modifiers: modifiers, binding: Binding::Button(button) }, form)
+            }
+            _ => self.text(&binding.to_string().to_lowercase(), form).unwrap_or_else(|| binding.to_string()),
+        }
+    }
+
+    /// Returns the name of a chord, such as "Ctrl+Shift+A".
+    pub fn chord_name(&self, chord: &Chord, form: NameForm) -> String {
+        let modifiers = [(chord.modifiers.ctrl, "modifier.ctrl"),
+                         (chord.modifiers.shift, "modifier.shift"),
+                         (chord.modifiers.alt, "modifier.alt"),
+                         (chord.modifiers.gui, "modifier.gui")];
+        let mut parts: Vec<String> = modifiers.iter()
+                                              .filter(|m| m.0)
+                                              .map(|&(_, id)| self.text(id, form).unwrap_or_default())
+                                              .collect();
+        parts.push(self.binding_name(chord.binding, form));
+        parts.join(&self.text("chord.separator", form).unwrap_or_default())
+    }
+
+    /// Returns the name of a sequence, such as "Ctrl+K Ctrl+C".
+    pub fn sequence_name(&self, sequence: &Sequence, form: NameForm) -> String {
+        let chords: Vec<String> = sequence.0.iter().map(|c| self.chord_name(c, form)).collect();
+        chords.join(&self.text("sequence.separator", form).unwrap_or_default())
+    }
+
+    fn key_name(&self, key: Key, form: NameForm) -> String {
+        let name = format!("{:?}", key);
+        if let Some(text) = self.text(&format!("key.{}", name.to_lowercase()), form) {
+            return text;
+        }
+        // Digit keys are named D0 to D9, and number pad keys NumPad0 and so on
+        if name.len() == 2 && name.starts_with('D') && name.as_bytes()[1].is_ascii_digit() {
+            name[1..].to_string()
+        } else if name.starts_with("NumPad") && name.len() > 6 {
+            match form {
+                NameForm::Short => format!("Num {}", &name[6..]),
+                NameForm::Long => format!("Numpad {}", &name[6..]),
+            }
+        } else {
+            name
+        }
+    }
+
+    fn mouse_name(&self, button: MouseButton, form: NameForm) -> String {
+        let id = format!("mouse.{:?}", button).to_lowercase();
+        self.text(&id, form).unwrap_or_else(|| format!("{:?}", button))
+    }
+
+    fn direction_name(&self, direction: GestureDirection, form: NameForm) -> String {
+        let id = format!("direction.{:?}", direction).to_lowercase();
+        self.text(&id, form).unwrap_or_else(|| format!("{:?}", direction))
+    }
+
+    /// Looks up the text with the id in the string table, then in the English names.
+    fn text(&self, id: &str, form: NameForm) -> Option<String> {
+        let table = match form {
+            NameForm::Short => self.table.0.get(&format!("{}.short", id)).or_else(|| self.table.0.get(id)),
+            NameForm::Long => self.table.0.get(id),
+        };
+        table.or_else(|| {
+            ENGLISH.iter().find(|e| e.0 == id).map(|&(_, short, long)| {
+                match form {
+                    NameForm::Short => short.to_string(),
+                    NameForm::Long => long.to_string(),
+                }
+            })
+        })
+    }
+}
+
+impl Default for ButtonNames {
+    fn default() -> Self {
+        Self::new()
+    }
+}
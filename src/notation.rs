@@ -0,0 +1,187 @@
+use {Binding, Chord, GamepadButton, Gesture, GestureDirection, Modifiers, ScrollDirection};
+use input::{Button, ControllerButton, Key, MouseButton};
+use std::error::Error;
+use std::fmt::{self, Display, Formatter};
+use std::str::FromStr;
+
+/// A binding which couldn't be parsed from its written name.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseBindingError {
+    /// The text which couldn't be parsed.
+    pub text: String
+}
+
+impl Display for ParseBindingError {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        write!(f, "unknown binding `{}`", self.text)
+    }
+}
+
+impl Error for ParseBindingError { }
+
+const MOUSE_BUTTONS: &'static [MouseButton] = &[MouseButton::Left, MouseButton::Right, MouseButton::Middle,
+                                                MouseButton::X1, MouseButton::X2, MouseButton::Button6,
+                                                MouseButton::Button7, MouseButton::Button8];
+
+const SCROLL_DIRECTIONS: &'static [ScrollDirection] = &[ScrollDirection::Up, ScrollDirection::Down,
+                                                        ScrollDirection::Left, ScrollDirection::Right];
+
+const GESTURE_DIRECTIONS: &'static [GestureDirection] = &[GestureDirection::Up, GestureDirection::Down,
+                                                          GestureDirection::Left, GestureDirection::Right];
+
+const GAMEPAD_BUTTONS: &'static [GamepadButton] = &[GamepadButton::South, GamepadButton::East,
+                                                    GamepadButton::West, GamepadButton::North,
+                                                    GamepadButton::LeftShoulder, GamepadButton::RightShoulder,
+                                                    GamepadButton::LeftTrigger, GamepadButton::RightTrigger,
+                                                    GamepadButton::LeftStick, GamepadButton::RightStick,
+                                                    GamepadButton::DPadUp, GamepadButton::DPadDown,
+                                                    GamepadButton::DPadLeft, GamepadButton::DPadRight,
+                                                    GamepadButton::Start, GamepadButton::Back, GamepadButton::Guide];
+
+/// Returns the value whose debug name matches the name, ignoring case.
+fn find_named<T: Copy + fmt::Debug>(values: &[T], name: &str) -> Option<T> {
+    values.iter().cloned().find(|v| format!("{:?}", v).eq_ignore_ascii_case(name))
+}
+
+/// Other common names of keys, which are accepted when parsing but never displayed.
+const KEY_ALIASES: &'static [(&'static str, Key)] = &[("Esc", Key::Escape), ("Enter", Key::Return),
+                                                      ("Del", Key::Delete), ("Ins", Key::Insert),
+                                                      ("PgUp", Key::PageUp), ("PgDn", Key::PageDown),
+                                                      ("Ctrl", Key::LCtrl), ("Control", Key::LCtrl),
+                                                      ("Shift", Key::LShift), ("Alt", Key::LAlt),
+                                                      ("Gui", Key::LGui), ("Spacebar", Key::Space)];
+
+/// Returns the key with the name, ignoring case, such as `Space` or `D1`. Aliases such as
+/// `Esc` are accepted, and a digit such as `1` is the key at the top of the keyboard.
+/// `Unknown` isn't a valid key name.
+pub fn parse_key(name: &str) -> Option<Key> {
+    if let Some(&(_, key)) = KEY_ALIASES.iter().find(|a| a.0.eq_ignore_ascii_case(name)) {
+        return Some(key);
+    } else if name.len() == 1 && name.as_bytes()[0].is_ascii_digit() {
+        return parse_key(&format!("D{}", name));
+    }
+
+    // The key codes are those of SDL, which are ASCII for printable keys, and scancodes
+    // with bit 30 set for the others
+    (0..0x80)
+        .chain(0x4000_0000..0x4000_0120)
+        .map(Key::from)
+        .filter(|&k| k != Key::Unknown)
+        .find(|k| format!("{:?}", k).eq_ignore_ascii_case(name))
+}
+
+/// Returns the written name of every binding, other than controller buttons and gestures
+/// of two strokes, so that a misspelled name can be corrected.
+pub fn binding_names() -> Vec<String> {
+    let keys = (0..0x80).chain(0x4000_0000..0x4000_0120).map(Key::from).filter(|&k| k != Key::Unknown);
+    let mut names: Vec<String> = keys.map(|k| format!("{:?}", k)).collect();
+    names.extend(MOUSE_BUTTONS.iter().map(|b| format!("Mouse.{:?}", b)));
+    names.extend(SCROLL_DIRECTIONS.iter().map(|d| format!("Scroll.{:?}", d)));
+    names.extend(MOUSE_BUTTONS.iter().map(|b| format!("DoubleClick.{:?}", b)));
+    names.extend(MOUSE_BUTTONS.iter().map(|b| format!("TripleClick.{:?}", b)));
+    names.extend(GESTURE_DIRECTIONS.iter().map(|d| format!("Gesture.{:?}", d)));
+    names.push("Gesture.Clockwise".to_string());
+    names.push("Gesture.Anticlockwise".to_string());
+    names.extend(GAMEPAD_BUTTONS.iter().map(|b| format!("Gamepad.{:?}", b)));
+    names
+}
+
+/// Returns the rest of the text if it starts with the prefix, ignoring case.
+fn strip_prefix<'a>(text: &'a str, prefix: &str) -> Option<&'a str> {
+    let n = prefix.len();
+    if text.len() >= n && text.is_char_boundary(n) && text[..n].eq_ignore_ascii_case(prefix) {
+        Some(&text[n..])
+    } else {
+        None
+    }
+}
+
+fn parse_button(name: &str) -> Option<Button> {
+    let mut parts = name.splitn(2, '.');
+    match (parts.next(), parts.next()) {
+        (Some(key), None) => parse_key(key).map(Button::Keyboard),
+        (Some(device), Some(button)) if device.eq_ignore_ascii_case("Mouse") => {
+            find_named(MOUSE_BUTTONS, button).map(Button::Mouse)
+        }
+        (Some(device), Some(button)) => {
+            let id = strip_prefix(device, "Pad").and_then(|id| id.parse().ok());
+            let button = strip_prefix(button, "Button").and_then(|b| b.parse().ok());
+            match (id, button) {
+                (Some(id), Some(button)) => Some(Button::Controller(ControllerButton { id: id, button: button })),
+                _ => None,
+            }
+        }
+        _ => None,
+    }
+}
+
+fn parse_gesture(name: &str) -> Option<Gesture> {
+    if name.eq_ignore_ascii_case("Clockwise") {
+        return Some(Gesture::ClockwiseCircle);
+    } else if name.eq_ignore_ascii_case("Anticlockwise") {
+        return Some(Gesture::AnticlockwiseCircle);
+    }
+    let mut strokes = name.splitn(2, '.').map(|d| find_named(GESTURE_DIRECTIONS, d));
+    match (strokes.next(), strokes.next()) {
+        (Some(Some(first)), None) => Some(Gesture::Stroke(first)),
+        (Some(Some(first)), Some(Some(second))) => Some(Gesture::Strokes(first, second)),
+        _ => None,
+    }
+}
+
+/// Bindings are written as the name of a key, such as `Space` or `D1`, or as a device and a
+/// button separated by a dot, such as `Mouse.Left`, `Pad0.Button3`, `Scroll.Up`,
+/// `DoubleClick.Left`, `Gesture.Up.Left`, `Gesture.Clockwise` or `Gamepad.South`. Chords
+/// are written as they are by `Chord`, such as `Ctrl+Shift+A` or `Alt+Mouse.Left`.
+impl Display for Binding {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            Binding::Button(Button::Keyboard(key)) => write!(f, "{:?}", key),
+            Binding::Button(Button::Mouse(button)) => write!(f, "Mouse.{:?}", button),
+            Binding::Button(Button::Controller(button)) => write!(f, "Pad{}.Button{}", button.id, button.button),
+            Binding::Scroll(direction) => write!(f, "Scroll.{:?}", direction),
+            Binding::DoubleClick(button) => write!(f, "DoubleClick.{:?}", button),
+            Binding::TripleClick(button) => write!(f, "TripleClick.{:?}", button),
+            Binding::Gesture(Gesture::Stroke(direction)) => write!(f, "Gesture.{:?}", direction),
+            Binding::Gesture(Gesture::Strokes(first, second)) => write!(f, "Gesture.{:?}.{:?}", first, second),
+            Binding::Gesture(Gesture::ClockwiseCircle) => write!(f, "Gesture.Clockwise"),
+            Binding::Gesture(Gesture::AnticlockwiseCircle) => write!(f, "Gesture.Anticlockwise"),
+            Binding::Gamepad(button) => write!(f, "Gamepad.{:?}", button),
+            Binding::Chord(modifiers, button) => {
+                write!(f, "{}", Chord { modifiers: modifiers, binding: Binding::Button(button) })
+            }
+        }
+    }
+}
+
+/// Parses a binding written as it is displayed. Names are matched ignoring case, and keys
+/// may also be written by common aliases, such as `Esc` or `Enter`. Only buttons can be
+/// pressed as chords, so `Ctrl+Scroll.Up` isn't a binding.
+impl FromStr for Binding {
+    type Err = ParseBindingError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let text = s.trim();
+        let binding = if text.contains('+') {
+            match text.parse::<Chord>() {
+                Ok(Chord { modifiers, binding: Binding::Button(button) }) if modifiers != Modifiers::default() => {
+                    Some(Binding::Chord(modifiers, button))
+                }
+                _ => None,
+            }
+        } else if let Some(name) = strip_prefix(text, "Scroll.") {
+            find_named(SCROLL_DIRECTIONS, name).map(Binding::Scroll)
+        } else if let Some(name) = strip_prefix(text, "DoubleClick.") {
+            find_named(MOUSE_BUTTONS, name).map(Binding::DoubleClick)
+        } else if let Some(name) = strip_prefix(text, "TripleClick.") {
+            find_named(MOUSE_BUTTONS, name).map(Binding::TripleClick)
+        } else if let Some(name) = strip_prefix(text, "Gesture.") {
+            parse_gesture(name).map(Binding::Gesture)
+        } else if let Some(name) = strip_prefix(text, "Gamepad.") {
+            find_named(GAMEPAD_BUTTONS, name).map(Binding::Gamepad)
+        } else {
+            parse_button(text).map(Binding::Button)
+        };
+        binding.ok_or_else(|| ParseBindingError { text: s.to_string() })
+    }
+}
@@ -0,0 +1,135 @@
+use {Action, Binding, ButtonTuple, MouseTranslationData};
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+use std::error::Error;
+use std::fmt::{self, Debug, Display, Formatter};
+
+/// The mouse settings which are saved in a `Profile`. Settings which are missing when
+/// it is deserialized keep their default values.
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Deserialize, Serialize))]
+#[cfg_attr(feature = "serde", serde(default))]
+pub struct MouseSettings {
+    /// Whether mouse motion along the x axis is inverted.
+    pub invert_x_motion: bool,
+
+    /// Whether mouse motion along the y axis is inverted.
+    pub invert_y_motion: bool,
+
+    /// Whether mouse scroll along the x axis is inverted.
+    pub invert_x_scroll: bool,
+
+    /// Whether mouse scroll along the y axis is inverted.
+    pub invert_y_scroll: bool,
+
+    /// The mouse sensitivity.
+    pub sensitivity: f64,
+
+    /// The scroll distance which makes up one step of a scroll binding.
+    pub scroll_step: f64,
+
+    /// The distance in pixels which the cursor must move while a mouse button is held for
+    /// a drag to start, or `None` if drags are not detected.
+    pub drag_threshold: Option<f64>,
+
+    /// The maximum time in seconds between consecutive clicks of a double or triple click.
+    pub multi_click_interval: f64,
+
+    /// The maximum distance in pixels which the cursor may move between consecutive clicks
+    /// of a double or triple click.
+    pub multi_click_distance: f64,
+
+    /// The distance in pixels from the edge of the window within which the cursor causes
+    /// edge scrolling, or `None` if edge scrolling is disabled.
+    pub edge_scroll_margin: Option<f64>
+}
+
+impl Default for MouseSettings {
+    fn default() -> Self {
+        mouse_settings(&MouseTranslationData::new((0, 0)))
+    }
+}
+
+/// A player's bindings and mouse settings, which can be saved and loaded again. Other
+/// settings, such as the viewport size and region bindings, are left out, because they
+/// belong to the application rather than the player.
+///
+/// With the `serde` feature, a profile can be serialized. The keymap is written as a map
+/// from the name of each action to a list of its bindings, written as they are displayed,
+/// such as `{"jump": ["Space", "Gamepad.South"]}`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Profile<A: Action> {
+    /// The buttons bound to each action, ordered by action.
+    pub keymap: Vec<(A, ButtonTuple)>,
+
+    /// The mouse settings.
+    pub mouse: MouseSettings
+}
+
+impl<A: Action> Profile<A> {
+    /// Checks that the profile can be used, which it can't if it binds the same binding to
+    /// more than one action, since a binding only triggers one action.
+    pub fn check(&self) -> Result<(), ProfileError<A>> {
+        for (i, &(action, ref buttons)) in self.keymap.iter().enumerate() {
+            for binding in buttons.into_iter().filter_map(|b| b) {
+                if let Some(&(other, _)) = self.keymap[i + 1..].iter().find(|&&(_, ref bt)| bt.contains(binding)) {
+                    return Err(ProfileError::DuplicateBinding(binding, action, other));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<A: Action> Default for Profile<A> {
+    fn default() -> Self {
+        Profile { keymap: vec![], mouse: Default::default() }
+    }
+}
+
+/// The reason that a `Profile` couldn't be used.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProfileError<A: Action> {
+    /// The binding is bound to both of the actions.
+    DuplicateBinding(Binding, A, A)
+}
+
+impl<A: Action + Debug> Display for ProfileError<A> {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        match *self {
+            ProfileError::DuplicateBinding(binding, first, second) => {
+                write!(f, "{} is bound to both {:?} and {:?}", binding, first, second)
+            }
+        }
+    }
+}
+
+impl<A: Action + Debug> Error for ProfileError<A> { }
+
+pub fn mouse_settings(data: &MouseTranslationData) -> MouseSettings {
+    MouseSettings {
+        invert_x_motion: data.x_axis_motion_inverted,
+        invert_y_motion: data.y_axis_motion_inverted,
+        invert_x_scroll: data.x_axis_scroll_inverted,
+        invert_y_scroll: data.y_axis_scroll_inverted,
+        sensitivity: data.sensitivity,
+        scroll_step: data.scroll_step,
+        drag_threshold: data.drag_threshold,
+        multi_click_interval: data.multi_click_interval,
+        multi_click_distance: data.multi_click_distance,
+        edge_scroll_margin: data.edge_scroll_margin
+    }
+}
+
+pub fn apply_mouse_settings(data: &mut MouseTranslationData, settings: &MouseSettings) {
+    data.x_axis_motion_inverted = settings.invert_x_motion;
+    data.y_axis_motion_inverted = settings.invert_y_motion;
+    data.x_axis_scroll_inverted = settings.invert_x_scroll;
+    data.y_axis_scroll_inverted = settings.invert_y_scroll;
+    data.sensitivity = settings.sensitivity;
+    data.scroll_step = settings.scroll_step;
+    data.drag_threshold = settings.drag_threshold;
+    data.multi_click_interval = settings.multi_click_interval;
+    data.multi_click_distance = settings.multi_click_distance;
+    data.edge_scroll_margin = settings.edge_scroll_margin;
+}
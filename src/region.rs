@@ -0,0 +1,68 @@
+use Action;
+use input::MouseButton;
+
+/// An area of the window, in the translated cursor coordinates produced by the
+/// `InputTranslator`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Region {
+    /// A rectangle, given as `[x, y, width, height]` where `x` and `y` are the coordinates
+    /// of its top left corner.
+    Rectangle([f64; 4]),
+
+    /// A circle, given by the coordinates of its centre and its radius.
+    Circle([f64; 2], f64)
+}
+
+impl Region {
+    /// Returns true if the position is inside the region. Positions on the edge of the
+    /// region are counted as inside.
+    pub fn contains(&self, pos: [f64; 2]) -> bool {
+        match *self {
+            Region::Rectangle([x, y, w, h]) => pos[0] >= x && pos[0] <= x + w && pos[1] >= y && pos[1] <= y + h,
+            Region::Circle(centre, radius) => {
+                let (dx, dy) = (pos[0] - centre[0], pos[1] - centre[1]);
+                dx * dx + dy * dy <= radius * radius
+            }
+        }
+    }
+}
+
+/// Binds a click of a mouse button inside a region of the window to an action. When the
+/// button is pressed, the region bindings containing the cursor take precedence over any
+/// binding of the button itself. If several overlapping regions contain the cursor, the
+/// one with the highest `z_order` is used, and of those the one added last.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RegionBinding<A: Action> {
+    /// The action which is triggered.
+    pub action: A,
+
+    /// The mouse button which must be clicked.
+    pub button: MouseButton,
+
+    /// The region which the cursor must be inside.
+    pub region: Region,
+
+    /// The stacking order of the region. Higher values are on top.
+    pub z_order: i32
+}
+
+impl<A: Action> RegionBinding<A> {
+    /// Creates a new region binding.
+    pub fn new(action: A, button: MouseButton, region: Region, z_order: i32) -> Self {
+        RegionBinding {
+            action: action,
+            button: button,
+            region: region,
+            z_order: z_order
+        }
+    }
+}
+
+/// Find the action bound to a click of the button at the position, taking the region
+/// with the highest z order.
+pub fn resolve<A: Action>(regions: &[RegionBinding<A>], button: MouseButton, pos: [f64; 2]) -> Option<A> {
+    regions.iter()
+           .filter(|r| r.button == button && r.region.contains(pos))
+           .max_by_key(|r| r.z_order)
+           .map(|r| r.action)
+}
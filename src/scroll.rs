@@ -0,0 +1,84 @@
+/// A direction which the scroll wheel can be moved in. Scrolling up or right corresponds to
+/// a positive scroll delta, after any inversion has been applied.
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum ScrollDirection {
+    /// Scrolling up, along the positive y axis.
+    Up,
+
+    /// Scrolling down, along the negative y axis.
+    Down,
+
+    /// Scrolling left, along the negative x axis.
+    Left,
+
+    /// Scrolling right, along the positive x axis.
+    Right
+}
+
+/// Accumulates scroll deltas, so that the fractional deltas produced by high resolution
+/// devices such as trackpads are turned into discrete steps.
+#[derive(Clone, Debug, PartialEq)]
+pub struct ScrollAccumulator {
+    x: f64,
+    y: f64,
+    axis: [f64; 2]
+}
+
+/// The most steps which a single scroll delta can produce in each direction. Any more are
+/// discarded, so that a bogus huge delta doesn't flood the game with presses.
+const MAX_STEPS: i64 = 16;
+
+impl ScrollAccumulator {
+    pub fn new() -> Self {
+        ScrollAccumulator { x: 0.0, y: 0.0, axis: [0.0, 0.0] }
+    }
+
+    /// Add a scroll delta, returning the direction of every step which has been completed.
+    /// The step size must be positive.
+    pub fn accumulate(&mut self, dx: f64, dy: f64, step: f64) -> Vec<ScrollDirection> {
+        let mut directions = vec![];
+        if step <= 0.0 {
+            return directions;
+        }
+        self.axis[0] += dx / step;
+        self.axis[1] += dy / step;
+
+        let steps_x = take_steps(&mut self.x, dx, step);
+        let steps_y = take_steps(&mut self.y, dy, step);
+
+        let x_dir = if steps_x > 0 { ScrollDirection::Right } else { ScrollDirection::Left };
+        let y_dir = if steps_y > 0 { ScrollDirection::Up } else { ScrollDirection::Down };
+        directions.extend((0..steps_x.abs()).map(|_| x_dir));
+        directions.extend((0..steps_y.abs()).map(|_| y_dir));
+        directions
+    }
+
+    /// Returns the distance scrolled along each axis since the axis was last reset, in
+    /// steps, including fractions of a step.
+    pub fn axis(&self) -> [f64; 2] {
+        self.axis
+    }
+
+    /// Reset the scroll axis to zero.
+    pub fn reset_axis(&mut self) {
+        self.axis = [0.0, 0.0];
+    }
+
+    /// Discard any partially accumulated steps, and reset the scroll axis.
+    pub fn reset(&mut self) {
+        self.x = 0.0;
+        self.y = 0.0;
+        self.reset_axis();
+    }
+}
+
+fn take_steps(accumulated: &mut f64, delta: f64, step: f64) -> i64 {
+    *accumulated += delta;
+    let steps = (*accumulated / step).trunc();
+    if steps.abs() > MAX_STEPS as f64 || !steps.is_finite() {
+        *accumulated = 0.0;
+        return if steps > 0.0 { MAX_STEPS } else if steps < 0.0 { -MAX_STEPS } else { 0 };
+    }
+    *accumulated -= steps * step;
+    steps as i64
+}
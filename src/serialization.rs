@@ -0,0 +1,120 @@
+use {Binding, ButtonTuple, Chord, NamedAction, Profile, ProfileError, Sequence};
+use profile::MouseSettings;
+use serde::{Deserialize, Serialize};
+use serde::de::{self, Deserializer};
+use serde::ser::{SerializeMap, SerializeStruct, Serializer};
+use std::collections::BTreeMap;
+
+/// Bindings are serialized as they are displayed, such as `"Mouse.Left"`, so that they
+/// can be edited by hand.
+impl Serialize for Binding {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Binding {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Chords are serialized as they are displayed, such as `"Ctrl+Shift+A"`.
+impl Serialize for Chord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Chord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+/// Sequences are serialized as they are displayed, such as `"Ctrl+K Ctrl+C"`.
+impl Serialize for Sequence {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for Sequence {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        text.parse().map_err(de::Error::custom)
+    }
+}
+
+/// A button tuple is serialized as a list of the buttons in it.
+impl<B: Copy + Serialize> Serialize for ButtonTuple<B> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.into_iter().filter_map(|b| b))
+    }
+}
+
+impl<'de, B: Copy + Deserialize<'de>> Deserialize<'de> for ButtonTuple<B> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let buttons = Vec::<B>::deserialize(deserializer)?;
+        if buttons.len() > 3 {
+            return Err(de::Error::invalid_length(buttons.len(), &"at most 3 buttons"));
+        }
+        let mut buttons = buttons.into_iter();
+        Ok(ButtonTuple(buttons.next(), buttons.next(), buttons.next()))
+    }
+}
+
+struct Keymap<'a, A: 'a + NamedAction>(&'a [(A, ButtonTuple)]);
+
+impl<'a, A: NamedAction> Serialize for Keymap<'a, A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut map = serializer.serialize_map(Some(self.0.len()))?;
+        for &(action, ref buttons) in self.0 {
+            map.serialize_entry(action.name(), buttons)?;
+        }
+        map.end()
+    }
+}
+
+impl<A: NamedAction> Serialize for Profile<A> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let mut profile = serializer.serialize_struct("Profile", 2)?;
+        profile.serialize_field("keymap", &Keymap(&self.keymap))?;
+        profile.serialize_field("mouse", &self.mouse)?;
+        profile.end()
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename = "Profile")]
+struct NamedProfile {
+    #[serde(default)]
+    keymap: BTreeMap<String, ButtonTuple>,
+    #[serde(default)]
+    mouse: MouseSettings
+}
+
+/// Deserializing a profile fails if it names an action which doesn't exist, or binds a
+/// binding to more than one action.
+impl<'de, A: NamedAction> Deserialize<'de> for Profile<A> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let named = NamedProfile::deserialize(deserializer)?;
+        let mut keymap = vec![];
+        for (name, buttons) in named.keymap {
+            match A::from_name(&name) {
+                Some(action) => keymap.push((action, buttons)),
+                None => return Err(de::Error::custom(format!("unknown action `{}`", name))),
+            }
+        }
+        keymap.sort_by(|a, b| a.0.cmp(&b.0));
+        let profile = Profile { keymap: keymap, mouse: named.mouse };
+        match profile.check() {
+            Ok(()) => Ok(profile),
+            Err(ProfileError::DuplicateBinding(binding, ..)) => {
+                Err(de::Error::custom(format!("`{}` is bound to more than one action", binding)))
+            }
+        }
+    }
+}
@@ -0,0 +1,340 @@
+use {Action, Modifiers, Translated};
+use chord;
+use click::ClickCounter;
+use controller::ControllerGuid;
+use device::DeviceClass;
+use drag::DragTracker;
+use gesture::GestureTracker;
+use input::{Button, Key};
+use scroll::{ScrollAccumulator, ScrollDirection};
+use virtual_cursor::VirtualCursor;
+
+/// Runtime state used by `InputTranslator::translate_stateful`. Keeps track of the
+/// buttons which are currently held, along with the action that each of them was
+/// pressed as, so that a matching `Release` can always be produced.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TranslationState<A: Action> {
+    drags: DragTracker<A>,
+    pub clicks: ClickCounter,
+    pub gestures: GestureTracker,
+    pub virtual_cursor: Option<VirtualCursor>,
+    held: Vec<HeldButton<A>>,
+    modifier_keys: Vec<Key>,
+    controllers: Vec<(i32, ControllerGuid)>,
+    last_device_class: Option<DeviceClass>,
+    pending_releases: Vec<Translated<A>>,
+    suppressed: Vec<Button>,
+    scroll: ScrollAccumulator,
+    cursor: Option<[f64; 2]>,
+    mouse_position: Option<[f64; 2]>,
+    warped: bool,
+    time: f64,
+    cursor_inside: bool,
+    has_focus: bool,
+    suppress_next_press: bool
+}
+
+impl<A: Action> TranslationState<A> {
+    pub fn new() -> Self {
+        TranslationState {
+            drags: DragTracker::new(),
+            clicks: ClickCounter::new(),
+            gestures: GestureTracker::new(),
+            virtual_cursor: None,
+            held: vec![],
+            modifier_keys: vec![],
+            controllers: vec![],
+            last_device_class: None,
+            pending_releases: vec![],
+            suppressed: vec![],
+            scroll: ScrollAccumulator::new(),
+            cursor: None,
+            mouse_position: None,
+            warped: false,
+            time: 0.0,
+            cursor_inside: true,
+            has_focus: true,
+            suppress_next_press: false
+        }
+    }
+
+    /// Returns true if the button is currently held.
+    pub fn is_held(&self, button: Button) -> bool {
+        self.held.iter().any(|h| h.button == button)
+    }
+
+    /// Record that the button has been pressed as the action. Pressing a button which is
+    /// already held, such as by the OS key repeat, restarts its hold time. Returns false if
+    /// the action is already held through another button, so that it isn't pressed again
+    /// without being released.
+    pub fn press(&mut self, button: Button, action: A) -> bool {
+        let held_elsewhere = self.held.iter().any(|h| h.action == action && h.button != button);
+        if self.is_held(button) {
+            self.repeat(button);
+        } else {
+            self.held.push(HeldButton { button: button, action: action, held_for: 0.0 });
+        }
+        !held_elsewhere
+    }
+
+    /// Record a repeated press of a held button, restarting its hold time, as it shows
+    /// that the button hasn't been released.
+    pub fn repeat(&mut self, button: Button) {
+        for h in self.held.iter_mut().filter(|h| h.button == button) {
+            h.held_for = 0.0;
+        }
+    }
+
+    /// Record that a button has been pressed or released, if it's a modifier key. Modifier
+    /// keys are followed whether or not they are bound.
+    pub fn modifier_changed(&mut self, button: Button, pressed: bool) {
+        if let Button::Keyboard(key) = button {
+            if chord::is_modifier(key) {
+                self.modifier_keys.retain(|&k| k != key);
+                if pressed {
+                    self.modifier_keys.push(key);
+                }
+            }
+        }
+    }
+
+    /// Returns the modifiers which are currently held.
+    pub fn modifiers(&self) -> Modifiers {
+        chord::modifiers_held(&self.modifier_keys)
+    }
+
+    /// Returns the held buttons, in the order that they were pressed.
+    pub fn held(&self) -> &[HeldButton<A>] {
+        &self.held
+    }
+
+    /// Record that the button has been released. Returns the `DragEnd` if it was being
+    /// dragged, and the `Release` of the action which it was pressed as if no other held
+    /// button was pressed as the same action.
+    pub fn release(&mut self, button: Button) -> Vec<Translated<A>> {
+        self.forget_where(|h| h.button == button)
+    }
+
+    /// Forget every held button which matches the predicate, in the order that they were
+    /// pressed. Returns a `DragEnd` for each of them which was being dragged, and a
+    /// `Release` of each distinct action which they were pressed as, leaving out any action
+    /// which is still held through another button. Every path which forgets held buttons
+    /// goes through here, so that drags are always ended, and an action is released once.
+    fn forget_where<F: Fn(&HeldButton<A>) -> bool>(&mut self, matches: F) -> Vec<Translated<A>> {
+        let (forgotten, held): (Vec<_>, Vec<_>) = self.held.drain(..).partition(|h| matches(h));
+        self.held = held;
+        let mut translated = Vec::with_capacity(forgotten.len());
+        for h in forgotten {
+            translated.extend(self.drags.end(h.button));
+            let release = Translated::Release(h.action);
+            if !translated.contains(&release) && !self.held.iter().any(|o| o.action == h.action) {
+                translated.push(release);
+            }
+        }
+        translated
+    }
+
+    /// Start tracking a drag of a mouse button which has just been pressed as the action.
+    pub fn begin_drag(&mut self, button: Button, action: A, cursor: Option<[f64; 2]>) {
+        self.drags.begin(button, action, cursor);
+    }
+
+    /// Update the drags with a new cursor position, returning any drag events it causes.
+    pub fn drag_motion(&mut self, pos: [f64; 2], threshold: f64) -> Vec<Translated<A>> {
+        self.drags.motion(pos, threshold)
+    }
+
+    /// Forget every held button which would no longer be translated to the action it was
+    /// pressed as, because its binding has changed. Their drags are ended and the actions
+    /// are released, by events which are queued to be retrieved with `take_pending_releases`.
+    pub fn invalidate_bindings<F: Fn(Button, A) -> bool>(&mut self, is_bound: F) {
+        for t in self.forget_where(|h| !is_bound(h.button, h.action)) {
+            if !self.pending_releases.contains(&t) {
+                self.pending_releases.push(t);
+            }
+        }
+    }
+
+    /// Returns the events which are waiting to be emitted, clearing the queue.
+    pub fn take_pending_releases(&mut self) -> Vec<Translated<A>> {
+        self.pending_releases.drain(..).collect()
+    }
+
+    /// Returns the total time in seconds which has been passed to `advance`.
+    pub fn time(&self) -> f64 {
+        self.time
+    }
+
+    /// Advance the clock, and the time that each button has been held for, by `dt` seconds.
+    pub fn advance(&mut self, dt: f64) {
+        self.time += dt;
+        for h in self.held.iter_mut() {
+            h.held_for += dt;
+        }
+    }
+
+    /// Forget every button which has been held for longer than `max_hold_time`, returning
+    /// the events which release them, like `release_all`.
+    pub fn release_stuck(&mut self, max_hold_time: f64) -> Vec<Translated<A>> {
+        self.forget_where(|h| h.held_for > max_hold_time)
+    }
+
+    /// Forget every held button, returning a `DragEnd` for each drag, and a `Release` of
+    /// each distinct action which was held, in the order that they were pressed.
+    pub fn release_all(&mut self) -> Vec<Translated<A>> {
+        self.forget_where(|_| true)
+    }
+
+    /// Forget every held button which matches the predicate, returning the events which
+    /// release them, like `release_all`.
+    pub fn release_where<F: Fn(Button) -> bool>(&mut self, matches: F) -> Vec<Translated<A>> {
+        self.forget_where(|h| matches(h.button))
+    }
+
+    /// Record that the controller has been connected.
+    pub fn connect_controller(&mut self, id: i32, guid: ControllerGuid) {
+        self.controllers.retain(|&(i, _)| i != id);
+        self.controllers.push((id, guid));
+    }
+
+    /// Record that the controller has been disconnected, returning its GUID if it was
+    /// known to be connected.
+    pub fn disconnect_controller(&mut self, id: i32) -> Option<ControllerGuid> {
+        match self.controllers.iter().position(|&(i, _)| i == id) {
+            Some(i) => Some(self.controllers.remove(i).1),
+            None => None,
+        }
+    }
+
+    /// Returns the id and GUID of each connected controller, in the order that they were
+    /// connected.
+    pub fn controllers(&self) -> &[(i32, ControllerGuid)] {
+        &self.controllers
+    }
+
+    /// Returns the class of device which was used most recently.
+    pub fn last_device_class(&self) -> Option<DeviceClass> {
+        self.last_device_class
+    }
+
+    /// Record that the class of device was used.
+    pub fn set_last_device_class(&mut self, class: DeviceClass) {
+        self.last_device_class = Some(class);
+    }
+
+    /// Update the focus state. Returns the events which end the drags and release the
+    /// actions which were held, because the window has lost focus.
+    pub fn set_focus(&mut self, focus: bool, suppress_after_focus: bool) -> Vec<Translated<A>> {
+        let had_focus = self.has_focus;
+        self.has_focus = focus;
+        if focus {
+            if !had_focus && suppress_after_focus {
+                self.suppress_next_press = true;
+            }
+            vec![]
+        } else {
+            self.suppressed.clear();
+            self.modifier_keys.clear();
+            self.mouse_position = None;
+            self.scroll.reset();
+            self.clicks.reset();
+            self.gestures.cancel();
+            self.release_all()
+        }
+    }
+
+    /// Returns the last known cursor position, in translated coordinates.
+    pub fn cursor(&self) -> Option<[f64; 2]> {
+        self.cursor
+    }
+
+    /// Record the cursor position, in translated coordinates.
+    pub fn set_cursor(&mut self, pos: [f64; 2]) {
+        self.cursor = Some(pos);
+    }
+
+    /// Record a position of the mouse cursor, in translated coordinates. Returns the distance
+    /// the mouse moved since its previous position, or `None` for the first position after a
+    /// call to `warp_cursor`. The mouse position is kept apart from the one set by
+    /// `set_cursor`, which may be the position of a virtual cursor.
+    pub fn mouse_motion(&mut self, pos: [f64; 2]) -> Option<[f64; 2]> {
+        let delta = self.mouse_position.map(|origin| [pos[0] - origin[0], pos[1] - origin[1]]);
+        self.mouse_position = Some(pos);
+        delta
+    }
+
+    /// Record that the application has warped the cursor, so the next cursor position is
+    /// where it was moved to rather than a movement of the mouse.
+    pub fn warp_cursor(&mut self) {
+        self.mouse_position = None;
+        self.warped = true;
+    }
+
+    /// Returns true if the cursor has been warped since the last call, clearing the flag.
+    pub fn take_cursor_warp(&mut self) -> bool {
+        let warped = self.warped;
+        self.warped = false;
+        warped
+    }
+
+    /// Record whether the cursor is inside the window.
+    pub fn set_cursor_inside(&mut self, inside: bool) {
+        self.cursor_inside = inside;
+        if !inside {
+            self.mouse_position = None;
+        }
+    }
+
+    /// Returns the cursor position if it is inside a focused window.
+    pub fn active_cursor(&self) -> Option<[f64; 2]> {
+        if self.cursor_inside && self.has_focus { self.cursor } else { None }
+    }
+
+    /// Accumulate a scroll delta, returning the direction of each completed scroll step.
+    pub fn scroll(&mut self, dx: f64, dy: f64, step: f64) -> Vec<ScrollDirection> {
+        self.scroll.accumulate(dx, dy, step)
+    }
+
+    /// Returns the distance scrolled since the scroll axis was last reset, in steps.
+    pub fn scroll_axis(&self) -> [f64; 2] {
+        self.scroll.axis()
+    }
+
+    /// Reset the scroll axis to zero.
+    pub fn reset_scroll_axis(&mut self) {
+        self.scroll.reset_axis();
+    }
+
+    /// Returns true if the press of this button should be swallowed because it is the
+    /// first input since the window regained focus. The matching release will be
+    /// swallowed as well.
+    pub fn suppress_press(&mut self, button: Button) -> bool {
+        if self.suppress_next_press {
+            self.suppress_next_press = false;
+            self.suppressed.push(button);
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Returns true if the release of this button should be swallowed because its press
+    /// was suppressed.
+    pub fn suppress_release(&mut self, button: Button) -> bool {
+        match self.suppressed.iter().position(|&b| b == button) {
+            Some(i) => {
+                self.suppressed.remove(i);
+                true
+            }
+            None => false,
+        }
+    }
+}
+
+/// A button which is held, and the action it was pressed as.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct HeldButton<A: Action> {
+    pub button: Button,
+    pub action: A,
+    pub held_for: f64
+}
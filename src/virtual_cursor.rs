@@ -0,0 +1,210 @@
+use input::{Button, ControllerAxisArgs};
+use window::Size;
+
+/// A software cursor, which can be driven by a controller stick, by four buttons (such as
+/// the arrow keys), or by the real mouse. When it is attached to an `InputTranslator`, its
+/// movements are reported as `Translated::Move(MouseCursor)` events, in the same way as
+/// the real cursor, so UI code does not need to know what moved it.
+///
+/// The cursor accelerates from rest to its full speed over the acceleration time, and is
+/// always kept within the viewport.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct VirtualCursor {
+    position: Option<[f64; 2]>,
+    speed: f64,
+    acceleration_time: f64,
+    deadzone: f64,
+    follow_mouse: bool,
+    stick: Option<(i32, u8, u8)>,
+    keys: Option<[Button; 4]>,
+    stick_input: [f64; 2],
+    keys_held: [bool; 4],
+    ramp: f64
+}
+
+impl VirtualCursor {
+    /// Creates a virtual cursor which follows the real mouse, and moves at 600 pixels per
+    /// second once it has accelerated for 0.25 seconds.
+    pub fn new() -> Self {
+        VirtualCursor {
+            position: None,
+            speed: 600.0,
+            acceleration_time: 0.25,
+            deadzone: 0.15,
+            follow_mouse: true,
+            stick: None,
+            keys: None,
+            stick_input: [0.0, 0.0],
+            keys_held: [false; 4],
+            ramp: 0.0
+        }
+    }
+
+    /// Drive the cursor with a controller stick, given the id of the controller and the
+    /// indices of its x and y axes.
+    pub fn with_stick(mut self, controller_id: i32, x_axis: u8, y_axis: u8) -> Self {
+        self.stick = Some((controller_id, x_axis, y_axis));
+        self
+    }
+
+    /// Drive the cursor with four buttons, which move it up, down, left and right.
+    pub fn with_keys(mut self, up: Button, down: Button, left: Button, right: Button) -> Self {
+        self.keys = Some([up, down, left, right]);
+        self
+    }
+
+    /// Set whether the cursor jumps to the position of the real mouse cursor when it moves.
+    pub fn follow_mouse(mut self, follow: bool) -> Self {
+        self.follow_mouse = follow;
+        self
+    }
+
+    /// Returns true if the cursor follows the real mouse cursor.
+    pub fn get_follow_mouse(&self) -> bool {
+        self.follow_mouse
+    }
+
+    /// Set the speed of the cursor in pixels per second, when the stick is fully deflected
+    /// or a key is held.
+    pub fn speed(mut self, speed: f64) -> Self {
+        self.speed = speed;
+        self
+    }
+
+    /// Returns the speed of the cursor in pixels per second.
+    pub fn get_speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Set the time in seconds which the cursor takes to accelerate to its full speed.
+    pub fn acceleration_time(mut self, time: f64) -> Self {
+        self.acceleration_time = time;
+        self
+    }
+
+    /// Returns the time in seconds which the cursor takes to accelerate to its full speed.
+    pub fn get_acceleration_time(&self) -> f64 {
+        self.acceleration_time
+    }
+
+    /// Set the stick deflection below which the stick is treated as centred.
+    pub fn deadzone(mut self, deadzone: f64) -> Self {
+        self.deadzone = deadzone;
+        self
+    }
+
+    /// Returns the stick deflection below which the stick is treated as centred.
+    pub fn get_deadzone(&self) -> f64 {
+        self.deadzone
+    }
+
+    /// Returns the position of the cursor, or `None` if it has not been placed yet. The
+    /// cursor is placed in the centre of the viewport on the first update.
+    pub fn get_position(&self) -> Option<[f64; 2]> {
+        self.position
+    }
+
+    /// Move the cursor to a position.
+    pub fn set_position(&mut self, position: [f64; 2]) {
+        self.position = Some(position);
+    }
+
+    /// Handle a movement of the real mouse cursor. Returns the new position of the virtual
+    /// cursor if it follows the mouse.
+    pub fn mouse_moved(&mut self, position: [f64; 2]) -> Option<[f64; 2]> {
+        if self.follow_mouse {
+            self.position = Some(position);
+            self.position
+        } else {
+            None
+        }
+    }
+
+    /// Handle a controller axis motion.
+    pub fn axis_moved(&mut self, args: ControllerAxisArgs) {
+        if let Some((id, x_axis, y_axis)) = self.stick {
+            if args.id == id && args.axis == x_axis {
+                self.stick_input[0] = args.position;
+            } else if args.id == id && args.axis == y_axis {
+                self.stick_input[1] = args.position;
+            }
+        }
+    }
+
+    /// Handle a button press or release.
+    pub fn button_changed(&mut self, button: Button, pressed: bool) {
+        if let Some(keys) = self.keys {
+            for (k, held) in keys.iter().zip(self.keys_held.iter_mut()) {
+                if *k == button {
+                    *held = pressed;
+                }
+            }
+        }
+    }
+
+    /// Release the keys and centre the stick, such as when the window loses focus.
+    pub fn reset_input(&mut self) {
+        self.stick_input = [0.0, 0.0];
+        self.keys_held = [false; 4];
+        self.ramp = 0.0;
+    }
+
+    /// Centre the stick if it is on the controller, which has been disconnected.
+    pub fn controller_disconnected(&mut self, controller_id: i32) {
+        if let Some((id, _, _)) = self.stick {
+            if id == controller_id {
+                self.stick_input = [0.0, 0.0];
+            }
+        }
+    }
+
+    /// Move the cursor according to the stick and keys over `dt` seconds, keeping it within
+    /// the viewport. Returns the new position if the cursor moved.
+    pub fn update(&mut self, dt: f64, viewport_size: Size) -> Option<[f64; 2]> {
+        let (w, h) = (viewport_size.width as f64, viewport_size.height as f64);
+        let old = match self.position {
+            Some(pos) => pos,
+            None => {
+                self.position = Some([w / 2.0, h / 2.0]);
+                return self.position;
+            }
+        };
+
+        let direction = self.direction();
+        if direction == [0.0, 0.0] {
+            self.ramp = 0.0;
+            return None;
+        }
+
+        self.ramp = if self.acceleration_time > 0.0 { (self.ramp + dt / self.acceleration_time).min(1.0) } else { 1.0 };
+        let distance = self.speed * self.ramp * dt;
+        let new = [(old[0] + direction[0] * distance).max(0.0).min(w),
+                   (old[1] + direction[1] * distance).max(0.0).min(h)];
+
+        self.position = Some(new);
+        if new != old { Some(new) } else { None }
+    }
+
+    /// The combined direction of the stick and keys, with a magnitude of at most one.
+    fn direction(&self) -> [f64; 2] {
+        let apply_deadzone = |v: f64| {
+            if v.abs() <= self.deadzone {
+                0.0
+            } else {
+                v.signum() * ((v.abs() - self.deadzone) / (1.0 - self.deadzone)).min(1.0)
+            }
+        };
+        let key = |held: bool| if held { 1.0 } else { 0.0 };
+
+        let x = apply_deadzone(self.stick_input[0]) + key(self.keys_held[3]) - key(self.keys_held[2]);
+        let y = apply_deadzone(self.stick_input[1]) + key(self.keys_held[1]) - key(self.keys_held[0]);
+        let len = (x * x + y * y).sqrt();
+        if len > 1.0 { [x / len, y / len] } else { [x, y] }
+    }
+}
+
+impl Default for VirtualCursor {
+    fn default() -> Self {
+        Self::new()
+    }
+}
@@ -2,6 +2,10 @@
 
 extern crate rebind;
 extern crate input;
+#[cfg(feature = "serde")]
+extern crate serde_json;
+#[cfg(feature = "serde")]
+extern crate toml;
 extern crate window;
 
 use input::Input;
@@ -160,3 +164,1429 @@ fn test_get_button_iter_len() {
     let _ = bti.next();
     assert_eq!(bti.len(), 0);
 }
+
+#[test]
+fn test_focus_loss_releases_held_actions() {
+    let mut translator = create_prepopulated_builder().build_translator();
+
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::W))),
+               vec![Translated::Press(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::Up))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::D))),
+               vec![Translated::Press(TestAction::Action4)]);
+
+    assert_eq!(translator.translate_stateful(&Input::Focus(false)),
+               vec![Translated::Release(TestAction::Action1), Translated::Release(TestAction::Action4)]);
+    assert!(!translator.is_held(Keyboard(Key::W)));
+
+    // The real release arrives after the synthetic one, and is ignored
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::W))), vec![]);
+}
+
+#[test]
+fn test_release_waits_for_every_held_button() {
+    let mut translator = create_prepopulated_builder().build_translator();
+
+    // W and Up are both bound to Action1, which is pressed once, and held until both are released
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::W))),
+               vec![Translated::Press(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::Up))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::W))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::Up))),
+               vec![Translated::Release(TestAction::Action1)]);
+
+    // The held buttons aren't compared
+    translator.translate_stateful(&Input::Press(Keyboard(Key::D)));
+    assert_eq!(translator, create_prepopulated_builder().build_translator());
+}
+
+#[test]
+fn test_suppress_first_input_after_focus() {
+    let mut translator = create_prepopulated_builder().suppress_input_after_focus(true).build_translator();
+
+    // Regaining focus without losing it first does not suppress anything
+    assert_eq!(translator.translate_stateful(&Input::Focus(true)), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::S))),
+               vec![Translated::Press(TestAction::Action2)]);
+
+    assert_eq!(translator.translate_stateful(&Input::Focus(false)),
+               vec![Translated::Release(TestAction::Action2)]);
+    assert_eq!(translator.translate_stateful(&Input::Focus(true)), vec![]);
+
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::A))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::D))),
+               vec![Translated::Press(TestAction::Action4)]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::A))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::D))),
+               vec![Translated::Release(TestAction::Action4)]);
+}
+
+#[test]
+fn test_filter_repeated_presses() {
+    let mut translator = create_prepopulated_builder().filter_repeated_presses(true).build_translator();
+
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::W))),
+               vec![Translated::Press(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::W))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::W))),
+               vec![Translated::Release(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::W))),
+               vec![Translated::Press(TestAction::Action1)]);
+}
+
+#[test]
+fn test_stuck_button_watchdog() {
+    use rebind::Diagnostic;
+    use std::sync::{Arc, Mutex};
+
+    let reports = Arc::new(Mutex::new(vec![]));
+    let callback_reports = reports.clone();
+    let mut translator = create_prepopulated_builder()
+                             .max_hold_time(Some(5.0))
+                             .filter_repeated_presses(true)
+                             .diagnostics_callback(move |d| callback_reports.lock().unwrap().push(*d))
+                             .build_translator();
+
+    translator.translate_stateful(&Input::Press(Keyboard(Key::Down)));
+    assert_eq!(translator.update(3.0), vec![]);
+    translator.translate_stateful(&Input::Press(Keyboard(Key::Left)));
+    assert_eq!(translator.update(3.0), vec![Translated::Release(TestAction::Action2)]);
+    assert!(translator.is_held(Keyboard(Key::Left)));
+
+    assert_eq!(*reports.lock().unwrap(),
+               vec![Diagnostic::StuckButton { button: Keyboard(Key::Down), held_for: 6.0 }]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::Down))), vec![]);
+
+    // The OS key repeat shows that a button is still held, even though it is filtered
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::Left))), vec![]);
+    assert_eq!(translator.update(3.0), vec![]);
+    assert!(translator.is_held(Keyboard(Key::Left)));
+}
+
+#[test]
+fn test_rebinding_held_button_releases_action() {
+    let mut translator = create_prepopulated_builder().build_translator();
+    translator.translate_stateful(&Input::Press(Keyboard(Key::W)));
+    translator.translate_stateful(&Input::Press(Keyboard(Key::S)));
+
+    // Moving W to a different action releases the action it was held as
+    assert_eq!(translator.insert_mapping(TestAction::Action5, Keyboard(Key::W)),
+               vec![Translated::Release(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::W))), vec![]);
+
+    assert_eq!(translator.remove_mapping(Keyboard(Key::S)),
+               (Some(TestAction::Action2), vec![Translated::Release(TestAction::Action2)]));
+}
+
+#[test]
+fn test_held_buttons_survive_conversion_to_rebind() {
+    let mut translator = create_prepopulated_builder().build_translator();
+    translator.translate_stateful(&Input::Press(Keyboard(Key::Up)));
+    translator.translate_stateful(&Input::Press(Keyboard(Key::Down)));
+
+    let mut rebind = translator.into_rebind();
+    rebind.insert_action_with_buttons(TestAction::Action1, ButtonTuple(Some(Keyboard(Key::I)), None, None));
+    let mut translator = rebind.into_translator();
+
+    assert_eq!(translator.update(0.0), vec![Translated::Release(TestAction::Action1)]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::Up))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Keyboard(Key::Down))),
+               vec![Translated::Release(TestAction::Action2)]);
+}
+
+#[test]
+fn test_scroll_bindings_accumulate_steps() {
+    use input::Motion;
+    use rebind::ScrollDirection;
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action6, ScrollDirection::Up)
+                             .with_mapping(TestAction::Action7, ScrollDirection::Down)
+                             .scroll_step(1.0)
+                             .build_translator();
+
+    let scroll = |y| Input::Move(Motion::MouseScroll(0.0, y));
+    assert_eq!(translator.translate_stateful(&scroll(0.6)),
+               vec![Translated::Move(Motion::MouseScroll(0.0, 0.6))]);
+    assert_eq!(translator.translate_stateful(&scroll(0.6)),
+               vec![Translated::Move(Motion::MouseScroll(0.0, 0.6)),
+                    Translated::Press(TestAction::Action6),
+                    Translated::Release(TestAction::Action6)]);
+    assert_eq!(translator.translate_stateful(&scroll(-2.2)),
+               vec![Translated::Move(Motion::MouseScroll(0.0, -2.2)),
+                    Translated::Press(TestAction::Action7),
+                    Translated::Release(TestAction::Action7),
+                    Translated::Press(TestAction::Action7),
+                    Translated::Release(TestAction::Action7)]);
+
+    // The scroll axis includes fractions of a step, until the next update
+    let axis = translator.get_scroll_axis();
+    assert!(axis[0] == 0.0 && (axis[1] + 1.0).abs() < 1e-9);
+    translator.update(0.1);
+    assert_eq!(translator.get_scroll_axis(), [0.0, 0.0]);
+
+    // A huge delta produces a limited number of steps
+    assert_eq!(translator.translate_stateful(&scroll(1e9)).len(), 1 + 2 * 16);
+    assert_eq!(translator.translate_stateful(&scroll(0.5)).len(), 1);
+
+    // Scroll bindings are left out of the buttons of an action
+    let mut rebind = translator.into_rebind();
+    rebind.insert_action_with_buttons(TestAction::Action6,
+                                      ButtonTuple(Some(ScrollDirection::Up.into()),
+                                                  Some(rebind::Binding::Button(Keyboard(Key::PageUp))),
+                                                  None));
+    assert_eq!(rebind.get_bindings(&TestAction::Action6).unwrap().buttons(),
+               ButtonTuple(Some(Keyboard(Key::PageUp)), None, None));
+}
+
+#[test]
+fn test_scroll_bindings_follow_inversion() {
+    use input::Motion;
+    use rebind::ScrollDirection;
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action6, ScrollDirection::Left)
+                             .x_scroll_inverted(true)
+                             .build_translator();
+
+    assert_eq!(translator.translate_stateful(&Input::Move(Motion::MouseScroll(1.0, 0.0))),
+               vec![Translated::Move(Motion::MouseScroll(-1.0, 0.0)),
+                    Translated::Press(TestAction::Action6),
+                    Translated::Release(TestAction::Action6)]);
+}
+
+#[test]
+fn test_mouse_drag_detection() {
+    use input::{Button, Motion, MouseButton};
+
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .with_mapping(TestAction::Action8, Button::Mouse(MouseButton::Left))
+                             .drag_threshold(Some(5.0))
+                             .y_motion_inverted(true)
+                             .build_translator();
+    let cursor = |x, y| Input::Move(Motion::MouseCursor(x, y));
+
+    translator.translate_stateful(&cursor(100.0, 500.0));
+    assert_eq!(translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Left))),
+               vec![Translated::Press(TestAction::Action8)]);
+
+    // Moving within the threshold does not start a drag
+    assert_eq!(translator.translate_stateful(&cursor(103.0, 500.0)),
+               vec![Translated::Move(Motion::MouseCursor(103.0, 100.0))]);
+
+    assert_eq!(translator.translate_stateful(&cursor(110.0, 500.0)),
+               vec![Translated::Move(Motion::MouseCursor(110.0, 100.0)),
+                    Translated::DragStart(TestAction::Action8, [100.0, 100.0]),
+                    Translated::DragUpdate(TestAction::Action8, [110.0, 100.0], [10.0, 0.0])]);
+    assert_eq!(translator.translate_stateful(&cursor(110.0, 490.0)),
+               vec![Translated::Move(Motion::MouseCursor(110.0, 110.0)),
+                    Translated::DragUpdate(TestAction::Action8, [110.0, 110.0], [0.0, 10.0])]);
+
+    assert_eq!(translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left))),
+               vec![Translated::DragEnd(TestAction::Action8, [110.0, 110.0]),
+                    Translated::Release(TestAction::Action8)]);
+}
+
+#[test]
+fn test_forgotten_buttons_end_their_drags() {
+    use input::{Button, Motion, MouseButton};
+
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .with_mapping(TestAction::Action8, Button::Mouse(MouseButton::Left))
+                             .drag_threshold(Some(5.0))
+                             .max_hold_time(Some(5.0))
+                             .build_translator();
+    let cursor = |x| Input::Move(Motion::MouseCursor(x, 100.0));
+    let start_drag = |translator: &mut TestTranslator| {
+        translator.translate_stateful(&cursor(100.0));
+        translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Left)));
+        translator.translate_stateful(&cursor(110.0));
+    };
+
+    // Unbinding the button
+    start_drag(&mut translator);
+    assert_eq!(translator.remove_mapping(Button::Mouse(MouseButton::Left)).1,
+               vec![Translated::DragEnd(TestAction::Action8, [110.0, 100.0]),
+                    Translated::Release(TestAction::Action8)]);
+    assert_eq!(translator.translate_stateful(&cursor(120.0)),
+               vec![Translated::Move(Motion::MouseCursor(120.0, 100.0))]);
+    translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left)));
+    translator.insert_mapping(TestAction::Action8, Button::Mouse(MouseButton::Left));
+
+    // The stuck button watchdog
+    start_drag(&mut translator);
+    assert_eq!(translator.update(6.0),
+               vec![Translated::DragEnd(TestAction::Action8, [110.0, 100.0]),
+                    Translated::Release(TestAction::Action8)]);
+    translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left)));
+
+    // Losing focus
+    start_drag(&mut translator);
+    assert_eq!(translator.translate_stateful(&Input::Focus(false)),
+               vec![Translated::DragEnd(TestAction::Action8, [110.0, 100.0]),
+                    Translated::Release(TestAction::Action8)]);
+    assert_eq!(translator.translate_stateful(&cursor(120.0)),
+               vec![Translated::Move(Motion::MouseCursor(120.0, 100.0))]);
+}
+
+#[test]
+fn test_double_and_triple_clicks() {
+    use input::{Button, Motion, MouseButton};
+    use rebind::Binding;
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action8, Button::Mouse(MouseButton::Left))
+                             .with_mapping(TestAction::Action9, Binding::DoubleClick(MouseButton::Left))
+                             .with_mapping(TestAction::Action10, Binding::TripleClick(MouseButton::Left))
+                             .multi_click_interval(0.3)
+                             .multi_click_distance(2.0)
+                             .build_translator();
+    let click = |translator: &mut TestTranslator| {
+        let mut translated = translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Left)));
+        translated.extend(translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left))));
+        translated
+    };
+
+    assert_eq!(click(&mut translator),
+               vec![Translated::Press(TestAction::Action8), Translated::Release(TestAction::Action8)]);
+    translator.update(0.2);
+    assert_eq!(click(&mut translator),
+               vec![Translated::Press(TestAction::Action8),
+                    Translated::DoubleClick(TestAction::Action9),
+                    Translated::Release(TestAction::Action8)]);
+    translator.update(0.2);
+    assert_eq!(click(&mut translator),
+               vec![Translated::Press(TestAction::Action8),
+                    Translated::TripleClick(TestAction::Action10),
+                    Translated::Release(TestAction::Action8)]);
+
+    // Too slow
+    translator.update(0.1);
+    click(&mut translator);
+    translator.update(0.31);
+    assert_eq!(click(&mut translator).len(), 2);
+
+    // Too far
+    translator.update(1.0);
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(10.0, 10.0)));
+    click(&mut translator);
+    translator.update(0.1);
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(10.0, 13.0)));
+    assert_eq!(click(&mut translator).len(), 2);
+}
+
+fn draw_gesture(translator: &mut TestTranslator, points: &[(f64, f64)]) -> Vec<Translated<TestAction>> {
+    use input::{Button, Motion, MouseButton};
+
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(points[0].0, points[0].1)));
+    translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Right)));
+    for &(x, y) in &points[1..] {
+        translator.translate_stateful(&Input::Move(Motion::MouseCursor(x, y)));
+    }
+    translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Right)))
+}
+
+#[test]
+fn test_gesture_strokes() {
+    use input::MouseButton;
+    use rebind::{Gesture, GestureDirection};
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action6, Gesture::Stroke(GestureDirection::Left))
+                             .with_mapping(TestAction::Action7,
+                                           Gesture::Strokes(GestureDirection::Down, GestureDirection::Right))
+                             .gesture_button(Some(MouseButton::Right))
+                             .gesture_min_stroke_length(40.0)
+                             .build_translator();
+
+    // A slightly wobbly stroke to the left
+    assert_eq!(draw_gesture(&mut translator, &[(300.0, 300.0), (270.0, 304.0), (240.0, 298.0), (200.0, 302.0)]),
+               vec![Translated::Press(TestAction::Action6), Translated::Release(TestAction::Action6)]);
+
+    assert_eq!(draw_gesture(&mut translator, &[(300.0, 300.0), (300.0, 350.0), (300.0, 400.0), (400.0, 400.0)]),
+               vec![Translated::Press(TestAction::Action7), Translated::Release(TestAction::Action7)]);
+
+    // Too short to be a stroke
+    assert_eq!(draw_gesture(&mut translator, &[(300.0, 300.0), (280.0, 300.0)]), vec![]);
+
+    // Recognised, but not bound
+    assert_eq!(draw_gesture(&mut translator, &[(300.0, 300.0), (300.0, 200.0)]), vec![]);
+}
+
+#[test]
+fn test_gesture_without_cursor_position() {
+    use input::{Button, MouseButton};
+    use rebind::{Gesture, GestureDirection};
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action6, Gesture::Stroke(GestureDirection::Left))
+                             .gesture_button(Some(MouseButton::Right))
+                             .gesture_min_stroke_length(0.0)
+                             .build_translator();
+
+    // The cursor hasn't moved, so no path was recorded at all
+    assert_eq!(translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Right))), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Right))), vec![]);
+}
+
+#[test]
+fn test_gesture_circle() {
+    use input::MouseButton;
+    use rebind::Gesture;
+    use std::f64::consts::PI;
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action8, Gesture::ClockwiseCircle)
+                             .with_mapping(TestAction::Action9, Gesture::AnticlockwiseCircle)
+                             .gesture_button(Some(MouseButton::Right))
+                             .build_translator();
+
+    let circle = |direction: f64| {
+        (0..33)
+            .map(|i| {
+                let angle = direction * 2.0 * PI * (i as f64) / 32.0;
+                (400.0 + 80.0 * angle.cos(), 300.0 + 80.0 * angle.sin())
+            })
+            .collect::<Vec<_>>()
+    };
+
+    assert_eq!(draw_gesture(&mut translator, &circle(1.0)),
+               vec![Translated::Press(TestAction::Action8), Translated::Release(TestAction::Action8)]);
+    assert_eq!(draw_gesture(&mut translator, &circle(-1.0)),
+               vec![Translated::Press(TestAction::Action9), Translated::Release(TestAction::Action9)]);
+}
+
+#[test]
+fn test_region_bindings() {
+    use input::{Button, Motion, MouseButton};
+    use rebind::Region;
+
+    let mut translator = create_prepopulated_builder()
+                             .with_mapping(TestAction::Action5, Button::Mouse(MouseButton::Left))
+                             .with_region_mapping(TestAction::Action6,
+                                                  MouseButton::Left,
+                                                  Region::Rectangle([0.0, 0.0, 200.0, 100.0]),
+                                                  0)
+                             .with_region_mapping(TestAction::Action7,
+                                                  MouseButton::Left,
+                                                  Region::Circle([200.0, 100.0], 30.0),
+                                                  1)
+                             .build_translator();
+    let mut click_at = |x, y| {
+        translator.translate_stateful(&Input::Move(Motion::MouseCursor(x, y)));
+        let pressed = translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Left)));
+        translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left)));
+        pressed
+    };
+
+    assert_eq!(click_at(50.0, 50.0), vec![Translated::Press(TestAction::Action6)]);
+    // The circle is on top of the rectangle where they overlap
+    assert_eq!(click_at(190.0, 90.0), vec![Translated::Press(TestAction::Action7)]);
+    assert_eq!(click_at(220.0, 110.0), vec![Translated::Press(TestAction::Action7)]);
+    // Outside of both regions, the plain button binding is used
+    assert_eq!(click_at(400.0, 400.0), vec![Translated::Press(TestAction::Action5)]);
+}
+
+#[test]
+fn test_region_release_matches_press() {
+    use input::{Button, Motion, MouseButton};
+    use rebind::{Region, RegionBinding};
+
+    let mut translator = create_prepopulated_builder().build_translator();
+    translator.insert_region_mapping(RegionBinding::new(TestAction::Action6,
+                                                        MouseButton::Left,
+                                                        Region::Rectangle([0.0, 0.0, 10.0, 10.0]),
+                                                        0));
+
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(5.0, 5.0)));
+    translator.translate_stateful(&Input::Press(Button::Mouse(MouseButton::Left)));
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(50.0, 50.0)));
+
+    // Unrelated rebinding does not release the region action
+    assert_eq!(translator.insert_mapping(TestAction::Action5, Keyboard(Key::Q)), vec![]);
+    assert_eq!(translator.translate_stateful(&Input::Release(Button::Mouse(MouseButton::Left))),
+               vec![Translated::Release(TestAction::Action6)]);
+}
+
+#[test]
+fn test_edge_scrolling() {
+    use input::Motion;
+
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .edge_scroll_margin(Some(20.0))
+                             .build_translator();
+    let mut edge_scroll_at = |x, y| {
+        translator.translate_stateful(&Input::Move(Motion::MouseCursor(x, y)));
+        translator.get_edge_scroll()
+    };
+
+    assert_eq!(edge_scroll_at(400.0, 300.0), [0.0, 0.0]);
+    assert_eq!(edge_scroll_at(10.0, 300.0), [-0.5, 0.0]);
+    assert_eq!(edge_scroll_at(0.0, 300.0), [-1.0, 0.0]);
+    assert_eq!(edge_scroll_at(795.0, 585.0), [0.75, 0.25]);
+    assert_eq!(edge_scroll_at(400.0, 5.0), [0.0, -0.75]);
+
+    translator.translate_stateful(&Input::Cursor(false));
+    assert_eq!(translator.get_edge_scroll(), [0.0, 0.0]);
+    translator.translate_stateful(&Input::Cursor(true));
+    assert_eq!(translator.get_edge_scroll(), [0.0, -0.75]);
+}
+
+#[test]
+fn test_synthesized_relative_motion() {
+    use input::Motion;
+
+    let mut translator = create_prepopulated_builder().synthesize_relative_motion(true).build_translator();
+    let cursor = |x, y| Input::Move(Motion::MouseCursor(x, y));
+
+    assert_eq!(translator.translate_stateful(&cursor(100.0, 100.0)),
+               vec![Translated::Move(Motion::MouseCursor(100.0, 100.0))]);
+    assert_eq!(translator.translate_stateful(&cursor(104.0, 97.0)),
+               vec![Translated::Move(Motion::MouseCursor(104.0, 97.0)),
+                    Translated::Move(Motion::MouseRelative(4.0, -3.0))]);
+
+    translator.notify_cursor_warp();
+    assert_eq!(translator.translate_stateful(&cursor(400.0, 300.0)),
+               vec![Translated::Move(Motion::MouseCursor(400.0, 300.0))]);
+    assert_eq!(translator.translate_stateful(&cursor(401.0, 300.0)),
+               vec![Translated::Move(Motion::MouseCursor(401.0, 300.0)),
+                    Translated::Move(Motion::MouseRelative(1.0, 0.0))]);
+
+    translator.translate_stateful(&Input::Cursor(false));
+    translator.translate_stateful(&Input::Cursor(true));
+    assert_eq!(translator.translate_stateful(&cursor(0.0, 0.0)),
+               vec![Translated::Move(Motion::MouseCursor(0.0, 0.0))]);
+}
+
+#[test]
+fn test_virtual_cursor_driven_by_keys() {
+    use input::Motion;
+    use rebind::VirtualCursor;
+
+    let cursor = VirtualCursor::new()
+                     .with_keys(Keyboard(Key::Up), Keyboard(Key::Down), Keyboard(Key::Left), Keyboard(Key::Right))
+                     .speed(100.0)
+                     .acceleration_time(1.0);
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .virtual_cursor(Some(cursor))
+                             .build_translator();
+
+    // The cursor starts in the middle of the viewport
+    assert_eq!(translator.update(0.5), vec![Translated::Move(Motion::MouseCursor(400.0, 300.0))]);
+    assert_eq!(translator.update(0.5), vec![]);
+
+    // Keys still translate to their actions, and accelerate the cursor
+    assert_eq!(translator.translate_stateful(&Input::Press(Keyboard(Key::Right))),
+               vec![Translated::Press(TestAction::Action4)]);
+    assert_eq!(translator.update(0.5), vec![Translated::Move(Motion::MouseCursor(425.0, 300.0))]);
+    assert_eq!(translator.update(0.5), vec![Translated::Move(Motion::MouseCursor(475.0, 300.0))]);
+    assert_eq!(translator.update(10.0), vec![Translated::Move(Motion::MouseCursor(800.0, 300.0))]);
+    assert_eq!(translator.update(1.0), vec![]);
+
+    translator.translate_stateful(&Input::Release(Keyboard(Key::Right)));
+    assert_eq!(translator.update(1.0), vec![]);
+    assert_eq!(translator.get_cursor_position(), Some([800.0, 300.0]));
+}
+
+#[test]
+fn test_virtual_cursor_driven_by_stick_and_mouse() {
+    use input::{ControllerAxisArgs, Motion};
+    use rebind::VirtualCursor;
+
+    let cursor = VirtualCursor::new().with_stick(0, 0, 1).speed(100.0).acceleration_time(0.0).deadzone(0.2);
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .virtual_cursor(Some(cursor))
+                             .build_translator();
+    let axis = |axis, position| {
+        Input::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 0, axis: axis, position: position }))
+    };
+
+    // The real mouse places the cursor
+    assert_eq!(translator.translate_stateful(&Input::Move(Motion::MouseCursor(10.0, 20.0))),
+               vec![Translated::Move(Motion::MouseCursor(10.0, 20.0))]);
+
+    // Within the deadzone
+    translator.translate_stateful(&axis(1, 0.1));
+    assert_eq!(translator.update(1.0), vec![]);
+
+    translator.translate_stateful(&axis(1, 0.6));
+    assert_eq!(translator.update(1.0), vec![Translated::Move(Motion::MouseCursor(10.0, 70.0))]);
+
+    translator.get_virtual_cursor_mut().unwrap().set_position([5.0, 5.0]);
+    translator.translate_stateful(&axis(1, -1.0));
+    assert_eq!(translator.update(1.0), vec![Translated::Move(Motion::MouseCursor(5.0, 0.0))]);
+}
+
+#[test]
+fn test_virtual_cursor_doesnt_synthesize_relative_motion() {
+    use input::{ControllerAxisArgs, Motion};
+    use rebind::VirtualCursor;
+
+    let cursor = VirtualCursor::new().with_stick(0, 0, 1).speed(100.0).acceleration_time(0.0).follow_mouse(false);
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE)
+                             .virtual_cursor(Some(cursor))
+                             .synthesize_relative_motion(true)
+                             .build_translator();
+    let axis = Input::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 0, axis: 0, position: 1.0 }));
+
+    // Neither the jump to the middle of the viewport nor the stick are mouse motion
+    assert_eq!(translator.update(0.5), vec![Translated::Move(Motion::MouseCursor(400.0, 300.0))]);
+    translator.translate_stateful(&axis);
+    assert_eq!(translator.update(1.0), vec![Translated::Move(Motion::MouseCursor(500.0, 300.0))]);
+
+    // The mouse still produces relative motion, though it doesn't move the cursor
+    let mouse = |x, y| Input::Move(Motion::MouseCursor(x, y));
+    assert_eq!(translator.translate_stateful(&mouse(10.0, 10.0)), vec![]);
+    assert_eq!(translator.translate_stateful(&mouse(13.0, 14.0)),
+               vec![Translated::Move(Motion::MouseRelative(3.0, 4.0))]);
+    assert_eq!(translator.get_cursor_position(), Some([500.0, 300.0]));
+}
+
+#[test]
+fn test_players_join_and_own_their_devices() {
+    use input::{Button, ControllerButton};
+    use rebind::{Device, KeyboardHalf, PlayerRouter, Routed};
+
+    let template = create_prepopulated_builder().build_translator();
+    let mut router = PlayerRouter::new(template);
+    let start = |id| Button::Controller(ControllerButton { id: id, button: 7 });
+    router.add_join_button(start(0));
+    router.add_join_button(Keyboard(Key::Space));
+    router.add_join_button(Keyboard(Key::Return));
+    router.set_split_keyboard(true);
+
+    // Nobody owns the keyboard yet
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::W))), vec![]);
+
+    assert_eq!(router.translate(&Input::Press(start(3))),
+               vec![Routed::Joined(0, Device::Controller(3))]);
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::Space))),
+               vec![Routed::Joined(1, Device::KeyboardHalf(KeyboardHalf::Left))]);
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::Return))),
+               vec![Routed::Joined(2, Device::KeyboardHalf(KeyboardHalf::Right))]);
+
+    // Each player has their own bindings
+    router.get_player_mut(2).unwrap().insert_mapping(TestAction::Action5, Keyboard(Key::Up));
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::W))),
+               vec![Routed::Player(1, Translated::Press(TestAction::Action1))]);
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::Up))),
+               vec![Routed::Player(2, Translated::Press(TestAction::Action5))]);
+
+    // Losing focus releases the actions of every player
+    assert_eq!(router.translate(&Input::Focus(false)),
+               vec![Routed::Player(1, Translated::Release(TestAction::Action1)),
+                    Routed::Player(2, Translated::Release(TestAction::Action5))]);
+}
+
+#[test]
+fn test_reassigning_a_device_releases_its_buttons() {
+    use rebind::{Device, KeyboardHalf, PlayerRouter, Routed};
+
+    let mut router = PlayerRouter::new(TestTranslator::new(TEST_SIZE));
+    let first = router.add_player(create_prepopulated_builder().build_translator());
+    let second = router.add_player(create_prepopulated_builder().build_translator());
+    router.assign_device(first, Device::Keyboard);
+    assert_eq!(router.get_owner(Device::KeyboardHalf(KeyboardHalf::Right)), Some(first));
+
+    router.translate(&Input::Press(Keyboard(Key::W)));
+    router.translate(&Input::Press(Keyboard(Key::Up)));
+    // Up is taken away, but W still holds the same action
+    assert_eq!(router.assign_device(second, Device::KeyboardHalf(KeyboardHalf::Right)), vec![]);
+    assert_eq!(router.get_devices(first), &[Device::KeyboardHalf(KeyboardHalf::Left)]);
+    assert!(router.get_player(first).unwrap().is_held(Keyboard(Key::W)));
+    assert!(!router.get_player(first).unwrap().is_held(Keyboard(Key::Up)));
+    assert_eq!(router.translate(&Input::Release(Keyboard(Key::W))),
+               vec![Routed::Player(first, Translated::Release(TestAction::Action1))]);
+
+    assert_eq!(router.translate(&Input::Press(Keyboard(Key::Down))),
+               vec![Routed::Player(second, Translated::Press(TestAction::Action2))]);
+}
+
+#[test]
+fn test_controller_disconnect_releases_held_actions() {
+    use input::{Button, ControllerButton};
+    use rebind::{ControllerEvent, ControllerGuid};
+
+    let pad = |button| Button::Controller(ControllerButton { id: 1, button: button });
+    let mut translator = create_prepopulated_builder().with_mapping(TestAction::Action5, pad(0)).build_translator();
+    let guid = ControllerGuid([3; 16]);
+
+    assert_eq!(translator.controller_event(ControllerEvent::Connected(1, guid)),
+               vec![Translated::ControllerConnected(1)]);
+    assert_eq!(translator.get_controllers(), &[(1, guid)]);
+
+    translator.translate_stateful(&Input::Press(pad(0)));
+    translator.translate_stateful(&Input::Press(Keyboard(Key::W)));
+    assert_eq!(translator.controller_event(ControllerEvent::Disconnected(1)),
+               vec![Translated::Release(TestAction::Action5), Translated::ControllerDisconnected(1)]);
+    assert_eq!(translator.get_controllers(), &[]);
+    assert!(translator.is_held(Keyboard(Key::W)));
+
+    // The release of the button arrives after the disconnection
+    assert_eq!(translator.translate_stateful(&Input::Release(pad(0))), vec![]);
+}
+
+#[test]
+fn test_reconnected_controller_returns_to_its_player() {
+    use input::{Button, ControllerButton};
+    use rebind::{ControllerEvent, ControllerGuid, Device, PlayerRouter, Routed};
+
+    let pad = |id, button| Button::Controller(ControllerButton { id: id, button: button });
+    let template = create_prepopulated_builder().with_mapping(TestAction::Action5, pad(0, 0)).build_translator();
+    let mut router = PlayerRouter::new(template);
+    router.add_join_button(pad(0, 7));
+    let (red, blue) = (ControllerGuid([1; 16]), ControllerGuid([2; 16]));
+
+    assert_eq!(router.controller_event(ControllerEvent::Connected(0, red)), vec![]);
+    assert_eq!(router.controller_event(ControllerEvent::Connected(1, blue)), vec![]);
+    router.translate(&Input::Press(pad(0, 7)));
+    // The translator of a player who joins is told about their controller, as id 0
+    assert_eq!(router.translate(&Input::Press(pad(1, 7))),
+               vec![Routed::Joined(1, Device::Controller(1)), Routed::Player(1, Translated::ControllerConnected(0))]);
+    assert_eq!(router.get_player(1).unwrap().get_controllers(), &[(0, blue)]);
+
+    router.translate(&Input::Press(pad(1, 0)));
+    // Every player's bindings refer to their own controller as controller 0
+    assert_eq!(router.controller_event(ControllerEvent::Disconnected(1)),
+               vec![Routed::Player(1, Translated::Release(TestAction::Action5)),
+                    Routed::Player(1, Translated::ControllerDisconnected(0))]);
+    assert_eq!(router.get_detached_players(), vec![1]);
+    assert_eq!(router.get_devices(1), &[]);
+
+    // A different model of controller doesn't take the place of the lost one
+    assert_eq!(router.controller_event(ControllerEvent::Connected(2, red)), vec![]);
+    assert_eq!(router.controller_event(ControllerEvent::Connected(3, blue)),
+               vec![Routed::Player(1, Translated::ControllerConnected(0))]);
+    assert_eq!(router.get_devices(1), &[Device::Controller(3)]);
+    assert_eq!(router.translate(&Input::Press(pad(3, 0))),
+               vec![Routed::Player(1, Translated::Press(TestAction::Action5))]);
+
+    // Taking the controller away disconnects it from the player's translator, and a player's
+    // second controller is known by the next local id
+    assert_eq!(router.assign_device(0, Device::Controller(3)),
+               vec![Routed::Player(1, Translated::Release(TestAction::Action5)),
+                    Routed::Player(1, Translated::ControllerDisconnected(0)),
+                    Routed::Player(0, Translated::ControllerConnected(1))]);
+    assert_eq!(router.get_player(1).unwrap().get_controllers(), &[]);
+    assert_eq!(router.get_player(0).unwrap().get_controllers(), &[(0, red), (1, blue)]);
+}
+
+#[test]
+fn test_player_with_two_controllers() {
+    use input::{Button, ControllerAxisArgs, ControllerButton, Motion};
+    use rebind::{ControllerEvent, ControllerGuid, Device, PlayerRouter, Routed};
+
+    let pad = |id, button| Button::Controller(ControllerButton { id: id, button: button });
+    let template = create_prepopulated_builder().with_mapping(TestAction::Action5, pad(0, 0))
+                                                .with_mapping(TestAction::Action6, pad(1, 0))
+                                                .build_translator();
+    let mut router = PlayerRouter::new(template.clone());
+    let player = router.add_player(template);
+    let (red, blue) = (ControllerGuid([1; 16]), ControllerGuid([2; 16]));
+    router.controller_event(ControllerEvent::Connected(4, red));
+    router.controller_event(ControllerEvent::Connected(7, blue));
+    router.assign_device(player, Device::Controller(4));
+    router.assign_device(player, Device::Controller(7));
+    assert_eq!(router.get_player(player).unwrap().get_controllers(), &[(0, red), (1, blue)]);
+
+    // Each controller keeps its own id, so their buttons and axes aren't merged
+    assert_eq!(router.translate(&Input::Press(pad(4, 0))),
+               vec![Routed::Player(player, Translated::Press(TestAction::Action5))]);
+    assert_eq!(router.translate(&Input::Press(pad(7, 0))),
+               vec![Routed::Player(player, Translated::Press(TestAction::Action6))]);
+    let axis = |id| Motion::ControllerAxis(ControllerAxisArgs { id: id, axis: 2, position: 0.5 });
+    assert_eq!(router.translate(&Input::Move(axis(7))), vec![Routed::Player(player, Translated::Move(axis(1)))]);
+
+    // Losing one controller leaves the other held and connected
+    assert_eq!(router.controller_event(ControllerEvent::Disconnected(7)),
+               vec![Routed::Player(player, Translated::Release(TestAction::Action6)),
+                    Routed::Player(player, Translated::ControllerDisconnected(1))]);
+    assert_eq!(router.get_player(player).unwrap().get_controllers(), &[(0, red)]);
+    assert!(router.get_player(player).unwrap().is_held(pad(0, 0)));
+
+    // The free local id is reused
+    assert_eq!(router.controller_event(ControllerEvent::Connected(8, blue)),
+               vec![Routed::Player(player, Translated::ControllerConnected(1))]);
+    assert_eq!(router.get_devices(player), &[Device::Controller(4), Device::Controller(8)]);
+}
+
+#[test]
+fn test_last_used_device_class() {
+    use input::{Button, ControllerAxisArgs, ControllerButton, Motion};
+    use rebind::DeviceClass;
+    use std::sync::{Arc, Mutex};
+
+    let changes = Arc::new(Mutex::new(vec![]));
+    let recorded = changes.clone();
+    let mut translator = create_prepopulated_builder().analog_noise_threshold(0.25)
+                                                      .device_class_callback(move |c| recorded.lock().unwrap().push(c))
+                                                      .build_translator();
+    let axis = |position| {
+        Input::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 0, axis: 0, position: position }))
+    };
+    assert_eq!(translator.get_last_device_class(), None);
+
+    // The first cursor position doesn't show that the mouse is being used
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(10.0, 10.0)));
+    translator.translate_stateful(&Input::Press(Keyboard(Key::W)));
+    translator.translate_stateful(&Input::Release(Keyboard(Key::W)));
+    assert_eq!(translator.get_last_device_class(), Some(DeviceClass::Keyboard));
+
+    // A drifting stick doesn't count
+    translator.translate_stateful(&axis(0.1));
+    translator.translate_stateful(&axis(-0.2));
+    assert_eq!(translator.get_last_device_class(), Some(DeviceClass::Keyboard));
+
+    translator.translate_stateful(&axis(0.8));
+    translator.translate_stateful(&Input::Press(Button::Controller(ControllerButton { id: 0, button: 1 })));
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(12.0, 10.0)));
+    translator.translate_stateful(&Input::Press(Keyboard(Key::S)));
+    assert_eq!(*changes.lock().unwrap(),
+               vec![DeviceClass::Keyboard, DeviceClass::Gamepad, DeviceClass::Mouse, DeviceClass::Keyboard]);
+
+    // Neither does the cursor being moved by the application
+    translator.notify_cursor_warp();
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(400.0, 300.0)));
+    assert_eq!(translator.get_last_device_class(), Some(DeviceClass::Keyboard));
+    translator.translate_stateful(&Input::Move(Motion::MouseCursor(402.0, 300.0)));
+    assert_eq!(translator.get_last_device_class(), Some(DeviceClass::Mouse));
+}
+
+#[test]
+fn test_gamepad_bindings_follow_each_controllers_mapping() {
+    use input::{Button, ControllerButton};
+    use rebind::{GamepadButton, GamepadMapping};
+
+    let pad = |id, button| Button::Controller(ControllerButton { id: id, button: button });
+    let mut translator = create_prepopulated_builder().with_mapping(TestAction::Action5, GamepadButton::South)
+                                                      .with_mapping(TestAction::Action6, GamepadButton::East)
+                                                      .with_mapping(TestAction::Action7, pad(1, 1))
+                                                      .gamepad_mapping(Some(GamepadMapping::sdl_game_controller()))
+                                                      .build_translator();
+    let mapping = GamepadMapping::new().with_button(2, GamepadButton::South).with_button(1, GamepadButton::East);
+    translator.set_gamepad_mapping(1, Some(mapping));
+
+    assert_eq!(translator.translate(&Input::Press(pad(0, 0))), Some(Translated::Press(TestAction::Action5)));
+    assert_eq!(translator.translate(&Input::Press(pad(0, 1))), Some(Translated::Press(TestAction::Action6)));
+    assert_eq!(translator.translate(&Input::Press(pad(1, 2))), Some(Translated::Press(TestAction::Action5)));
+    assert_eq!(translator.get_gamepad_button(pad(1, 0)), None);
+    assert_eq!(translator.get_glyph_id(rebind::Binding::Button(pad(1, 2))), Some("face_south"));
+    assert_eq!(translator.get_glyph_id(rebind::Binding::Button(pad(1, 0))), None);
+    assert_eq!(translator.get_glyph_id(GamepadButton::East.into()), Some("face_east"));
+
+    // A binding of the raw button takes precedence
+    assert_eq!(translator.translate(&Input::Press(pad(1, 1))), Some(Translated::Press(TestAction::Action7)));
+
+    translator.set_swap_confirm_cancel(true);
+    assert_eq!(translator.translate_stateful(&Input::Press(pad(0, 0))), vec![Translated::Press(TestAction::Action6)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(pad(0, 1))), vec![Translated::Press(TestAction::Action5)]);
+    assert_eq!(translator.translate_stateful(&Input::Release(pad(0, 0))),
+               vec![Translated::Release(TestAction::Action6)]);
+
+    // Buttons which are held as the action they used to be mapped to are released
+    assert_eq!(translator.set_swap_confirm_cancel(false), vec![Translated::Release(TestAction::Action5)]);
+    assert_eq!(translator.translate_stateful(&Input::Press(pad(1, 2))), vec![Translated::Press(TestAction::Action5)]);
+    assert_eq!(translator.set_gamepad_mapping(1, None), vec![Translated::Release(TestAction::Action5)]);
+}
+
+#[test]
+fn test_parse_game_controller_db() {
+    use rebind::{ControllerGuid, GameControllerDb, GameControllerDbError, GameControllerDbErrorKind, GamepadAxis,
+                 GamepadButton};
+
+    let text = "# Game controller mappings\n\
+                030000005e0400008e02000000000000,Xbox 360 Controller,a:b0,b:b1,x:b2,y:b3,back:b6,start:b7,\
+                leftx:a0,lefty:a1,lefttrigger:a2,dpup:h0.1,+righty:+a4,platform:Linux,\n\
+                030000005e0400008e02000000000000,Xbox 360 Controller,a:b1,platform:Windows,\n\
+                \n\
+                not-a-guid,Broken,a:b0,\n\
+                030000005e0400008e02000000000001,\n\
+                030000005e0400008e02000000000002,Broken,a:q0,\n\
+                030000005e0400008e02000000000003,Broken,leftx,\n";
+    let mut db = GameControllerDb::new();
+    db.set_platform(Some("Linux"));
+    let errors = db.add_mappings(text);
+    let error = |line, kind| GameControllerDbError { line: line, kind: kind };
+    assert_eq!(errors,
+               vec![error(5, GameControllerDbErrorKind::InvalidGuid("not-a-guid".to_string())),
+                    error(6, GameControllerDbErrorKind::MissingName),
+                    error(7, GameControllerDbErrorKind::InvalidElement("a:q0".to_string())),
+                    error(8, GameControllerDbErrorKind::InvalidElement("leftx".to_string()))]);
+    assert_eq!(errors[2].to_string(), "line 7: invalid mapping element `a:q0`");
+    assert_eq!(db.len(), 2);
+
+    let guid = ControllerGuid::from_hex("030000005e0400008e02000000000000").unwrap();
+    let entry = db.get(guid).unwrap();
+    assert_eq!(entry.name, "Xbox 360 Controller");
+    assert_eq!(entry.platform, Some("Linux".to_string()));
+    assert_eq!(entry.mapping.get_button(0), Some(GamepadButton::South));
+    assert_eq!(entry.mapping.get_button(7), Some(GamepadButton::Start));
+    assert_eq!(entry.mapping.get_axis(2), Some(GamepadAxis::LeftTrigger));
+    assert_eq!(entry.mapping.get_raw_axis(GamepadAxis::RightY), None);
+    assert_eq!(entry.skipped, vec!["dpup:h0.1", "+righty:+a4"]);
+    assert_eq!(guid.to_string(), "030000005e0400008e02000000000000");
+
+    db.set_platform(Some("Windows"));
+    assert_eq!(db.get_mapping(guid).unwrap().get_button(1), Some(GamepadButton::South));
+    assert_eq!(db.get(ControllerGuid([0; 16])), None);
+}
+
+#[test]
+fn test_gamepad_database_maps_connected_controllers() {
+    use input::{Button, ControllerButton};
+    use rebind::{ControllerEvent, ControllerGuid, GameControllerDb, GamepadButton};
+
+    let mut db = GameControllerDb::new();
+    db.set_platform(None);
+    db.add_mappings("050000004c050000c405000000010000,PS4 Controller,a:b1,b:b2,x:b0,y:b3,");
+    let guid = ControllerGuid::from_hex("050000004c050000c405000000010000").unwrap();
+    let mut translator = create_prepopulated_builder().with_mapping(TestAction::Action5, GamepadButton::South)
+                                                      .gamepad_database(Some(db))
+                                                      .build_translator();
+    let pad = |button| Button::Controller(ControllerButton { id: 4, button: button });
+
+    assert_eq!(translator.translate(&Input::Press(pad(1))), None);
+    translator.controller_event(ControllerEvent::Connected(4, guid));
+    assert_eq!(translator.translate(&Input::Press(pad(1))), Some(Translated::Press(TestAction::Action5)));
+
+    // The mapping doesn't outlive the controller, or pass to another model with the same id
+    translator.controller_event(ControllerEvent::Disconnected(4));
+    assert_eq!(translator.get_gamepad_mapping(4), None);
+    translator.controller_event(ControllerEvent::Connected(4, guid));
+    translator.controller_event(ControllerEvent::Connected(4, ControllerGuid([9; 16])));
+    assert_eq!(translator.translate(&Input::Press(pad(1))), None);
+}
+
+#[test]
+fn test_calibration_wizard() {
+    use input::ControllerAxisArgs;
+    use rebind::{CalibrationStep, CalibrationStore, CalibrationStoreError, CalibrationWizard, ControllerGuid};
+
+    let axis = |id, axis, position| ControllerAxisArgs { id: id, axis: axis, position: position };
+    let mut wizard = CalibrationWizard::new(1);
+    assert_eq!(wizard.get_step(), CalibrationStep::Center);
+
+    // The stick rests off centre
+    wizard.axis_moved(axis(1, 0, 0.5));
+    wizard.axis_moved(axis(1, 0, 0.25));
+    wizard.axis_moved(axis(0, 0, 0.9));
+    assert_eq!(wizard.get_calibration(), None);
+    assert_eq!(wizard.advance(), CalibrationStep::Range);
+
+    for &p in &[0.5, 0.75, -0.25, -0.5, 0.125] {
+        wizard.axis_moved(axis(1, 0, p));
+    }
+    // Barely touched, so it is left out
+    wizard.axis_moved(axis(1, 1, 0.1));
+    assert_eq!(wizard.advance(), CalibrationStep::Done);
+
+    let calibration = wizard.get_calibration().unwrap();
+    let x = calibration.get_axis(0).unwrap();
+    assert_eq!((x.center, x.min, x.max), (0.25, -0.5, 0.75));
+    assert_eq!(calibration.get_axis(1), None);
+    assert_eq!(x.apply(0.25), 0.0);
+    assert_eq!(x.apply(0.75), 1.0);
+    assert_eq!(x.apply(-0.125), -0.5);
+    assert_eq!(x.apply(-1.0), -1.0);
+
+    let guid = ControllerGuid([7; 16]);
+    let mut store = CalibrationStore::new();
+    store.insert(guid, calibration.clone());
+    let text = store.to_string();
+    assert_eq!(text, "07070707070707070707070707070707,a0:0.25:-0.5:0.75\n");
+    let loaded = CalibrationStore::parse(&text).unwrap();
+    assert_eq!(loaded.get(guid), Some(&calibration));
+    assert_eq!(CalibrationStore::parse("# saved\n07070707070707070707070707070707,a1:0:1\n").unwrap_err(),
+               CalibrationStoreError { line: 2, field: "a1:0:1".to_string() });
+    assert!(CalibrationStore::parse("0707,a0:0:-1:1").is_err());
+}
+
+#[test]
+fn test_calibration_is_applied_before_the_deadzone() {
+    use input::{ControllerAxisArgs, Motion};
+    use rebind::{AxisCalibration, CalibrationStore, ControllerEvent, ControllerGuid, DeviceCalibration,
+                 VirtualCursor};
+
+    let guid = ControllerGuid([7; 16]);
+    let mut store = CalibrationStore::new();
+    store.insert(guid, DeviceCalibration::new().with_axis(0, AxisCalibration { center: 0.4, min: -0.6, max: 0.9 }));
+    let cursor = VirtualCursor::new().with_stick(2, 0, 1).speed(100.0).acceleration_time(0.0).deadzone(0.2);
+    let mut translator = create_prepopulated_builder_with_size(TEST_SIZE).virtual_cursor(Some(cursor))
+                                                                         .calibration_store(Some(store))
+                                                                         .build_translator();
+    let axis = |position| {
+        Input::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 2, axis: 0, position: position }))
+    };
+    translator.update(0.0);
+
+    // Without a calibration, the resting stick is outside the deadzone and pushes the cursor
+    translator.translate_stateful(&axis(0.4));
+    assert_eq!(translator.update(1.0), vec![Translated::Move(Motion::MouseCursor(425.0, 300.0))]);
+
+    translator.controller_event(ControllerEvent::Connected(2, guid));
+    assert_eq!(translator.translate_stateful(&axis(0.4)),
+               vec![Translated::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 2, axis: 0, position: 0.0 }))]);
+    assert_eq!(translator.update(1.0), vec![]);
+    assert_eq!(translator.translate(&axis(-0.1)),
+               Some(Translated::Move(Motion::ControllerAxis(ControllerAxisArgs { id: 2, axis: 0, position: -0.5 }))));
+
+    // Neither disconnecting nor another model with the same id keeps the calibration
+    translator.controller_event(ControllerEvent::Disconnected(2));
+    assert_eq!(translator.get_calibration(2), None);
+    translator.controller_event(ControllerEvent::Connected(2, guid));
+    translator.controller_event(ControllerEvent::Connected(2, ControllerGuid([8; 16])));
+    assert_eq!(translator.get_calibration(2), None);
+}
+
+#[derive(Clone, Copy, Debug, Eq, Hash, PartialEq, Ord, PartialOrd)]
+enum MenuAction {
+    Confirm,
+    Cancel,
+    Pause
+}
+
+impl Action for MenuAction { }
+
+impl rebind::NamedAction for MenuAction {
+    fn all() -> &'static [Self] {
+        static ALL: &'static [MenuAction] = &[MenuAction::Confirm, MenuAction::Cancel, MenuAction::Pause];
+        ALL
+    }
+
+    fn name(&self) -> &'static str {
+        match *self {
+            MenuAction::Confirm => "confirm",
+            MenuAction::Cancel => "cancel",
+            MenuAction::Pause => "pause",
+        }
+    }
+}
+
+#[test]
+fn test_unbound_actions_are_listed() {
+    use rebind::{Binding, NamedAction, Region, RegionBinding};
+    use input::MouseButton;
+
+    assert_eq!(MenuAction::from_name("cancel"), Some(MenuAction::Cancel));
+    assert_eq!(MenuAction::from_name("Cancel"), None);
+
+    let builder = Builder::new(TEST_SIZE).with_mapping(MenuAction::Confirm, Keyboard(Key::Return));
+    let mut rebind: InputRebind<MenuAction> = builder.into();
+    assert_eq!(rebind.get_unbound_actions(), vec![MenuAction::Cancel, MenuAction::Pause]);
+    assert_eq!(rebind.get_all_bindings(),
+               vec![(MenuAction::Confirm, ButtonTuple(Some(Binding::Button(Keyboard(Key::Return))), None, None)),
+                    (MenuAction::Cancel, ButtonTuple::new()),
+                    (MenuAction::Pause, ButtonTuple::new())]);
+
+    let pause = RegionBinding::new(MenuAction::Pause, MouseButton::Left, Region::Rectangle([0.0, 0.0, 10.0, 10.0]), 0);
+    rebind.insert_region_mapping(pause);
+    rebind.insert_action(MenuAction::Confirm);
+    assert_eq!(rebind.get_unbound_actions(), vec![MenuAction::Confirm, MenuAction::Cancel]);
+}
+
+impl rebind::ActionInfo for MenuAction {
+    fn display_name_key(&self) -> &'static str {
+        match *self {
+            MenuAction::Confirm => "action.confirm",
+            MenuAction::Cancel => "action.cancel",
+            MenuAction::Pause => "action.pause",
+        }
+    }
+
+    fn category(&self) -> Option<&'static str> {
+        match *self {
+            MenuAction::Pause => None,
+            _ => Some("menu"),
+        }
+    }
+
+    fn is_rebindable(&self) -> bool {
+        *self != MenuAction::Pause
+    }
+}
+
+#[test]
+fn test_locked_actions_cannot_be_rebound() {
+    use rebind::{Binding, RebindError};
+
+    let escape = Binding::Button(Keyboard(Key::Escape));
+    let enter = Binding::Button(Keyboard(Key::Return));
+    let builder = Builder::new(TEST_SIZE).with_mapping(MenuAction::Pause, Keyboard(Key::Escape))
+                                         .with_mapping(MenuAction::Confirm, Keyboard(Key::Return))
+                                         .with_mapping(MenuAction::Cancel, Keyboard(Key::Backspace));
+    let mut rebind: InputRebind<MenuAction> = builder.into();
+
+    assert_eq!(rebind.rebind_action(MenuAction::Pause, ButtonTuple(Some(enter), None, None)),
+               Err(RebindError::Locked(MenuAction::Pause)));
+    assert_eq!(rebind.rebind_action(MenuAction::Cancel, ButtonTuple(Some(escape), None, None)),
+               Err(RebindError::Conflict(MenuAction::Pause, escape)));
+
+    // Rebinding takes the button away from the action it was bound to before
+    assert_eq!(rebind.rebind_action(MenuAction::Cancel, ButtonTuple(Some(enter), None, None)),
+               Ok(ButtonTuple(Some(Binding::Button(Keyboard(Key::Backspace))), None, None)));
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm), Some(&ButtonTuple::new()));
+    assert_eq!(rebind.get_bindings(&MenuAction::Pause), Some(&ButtonTuple(Some(escape), None, None)));
+
+    let categories = rebind.get_bindings_by_category();
+    assert_eq!(categories,
+               vec![(Some("menu"),
+                     vec![(MenuAction::Confirm, ButtonTuple::new()),
+                          (MenuAction::Cancel, ButtonTuple(Some(enter), None, None))]),
+                    (None, vec![(MenuAction::Pause, ButtonTuple(Some(escape), None, None))])]);
+}
+
+#[test]
+fn test_binding_names() {
+    use input::{Button, ControllerButton, MouseButton};
+    use rebind::{Binding, GamepadButton, Gesture, GestureDirection, ParseBindingError, ScrollDirection};
+
+    let bindings = [(Binding::Button(Keyboard(Key::Space)), "Space"),
+                    (Binding::Button(Keyboard(Key::D1)), "D1"),
+                    (Binding::Button(Button::Mouse(MouseButton::Left)), "Mouse.Left"),
+                    (Binding::Button(Button::Controller(ControllerButton { id: 0, button: 3 })), "Pad0.Button3"),
+                    (Binding::Scroll(ScrollDirection::Up), "Scroll.Up"),
+                    (Binding::DoubleClick(MouseButton::Right), "DoubleClick.Right"),
+                    (Binding::Gesture(Gesture::Strokes(GestureDirection::Down, GestureDirection::Right)),
+                     "Gesture.Down.Right"),
+                    (Binding::Gesture(Gesture::ClockwiseCircle), "Gesture.Clockwise"),
+                    (Binding::Gamepad(GamepadButton::South), "Gamepad.South")];
+    for &(binding, name) in &bindings {
+        assert_eq!(binding.to_string(), name);
+        assert_eq!(name.parse(), Ok(binding));
+    }
+
+    assert_eq!(" mouse.left ".parse(), Ok(Binding::Button(Button::Mouse(MouseButton::Left))));
+    assert_eq!("Spcae".parse::<Binding>(), Err(ParseBindingError { text: "Spcae".to_string() }));
+    assert!("Unknown".parse::<Binding>().is_err());
+}
+
+#[test]
+fn test_chord_notation() {
+    use input::{Button, MouseButton};
+    use rebind::{Binding, Chord, Modifiers, ParseBindingError, Sequence};
+
+    let ctrl_shift = Modifiers { ctrl: true, shift: true, ..Default::default() };
+    let chord = Chord { modifiers: ctrl_shift, binding: Binding::Button(Keyboard(Key::A)) };
+    assert_eq!(chord.to_string(), "Ctrl+Shift+A");
+    assert_eq!("Ctrl+Shift+A".parse(), Ok(chord));
+    assert_eq!("shift+control+a".parse(), Ok(chord));
+
+    let left_click = Binding::Button(Button::Mouse(MouseButton::Left));
+    let alt_click = Chord { modifiers: Modifiers { alt: true, ..Default::default() }, binding: left_click };
+    assert_eq!(alt_click.to_string(), "Alt+Mouse.Left");
+    assert_eq!(Chord::from(left_click).to_string(), "Mouse.Left");
+
+    // Aliases are parsed, but the canonical names are displayed
+    let escape = Binding::Button(Keyboard(Key::Escape));
+    assert_eq!("Esc".parse(), Ok(escape));
+    assert_eq!("Enter".parse(), Ok(Binding::Button(Keyboard(Key::Return))));
+    assert_eq!("1".parse(), Ok(Binding::Button(Keyboard(Key::D1))));
+    assert_eq!("Cmd+Esc".parse::<Chord>().unwrap().to_string(), "Gui+Escape");
+    assert_eq!("Ctrl".parse::<Chord>().unwrap().to_string(), "LCtrl");
+    assert!("Ctrl+Shift".parse::<Chord>().is_err());
+
+    // Chords of buttons are bindings
+    let ctrl_shift_a = Binding::Chord(ctrl_shift, Button::Keyboard(Key::A));
+    assert_eq!(ctrl_shift_a.to_string(), "Ctrl+Shift+A");
+    assert_eq!("shift+ctrl+a".parse(), Ok(ctrl_shift_a));
+    assert!("Ctrl+Scroll.Up".parse::<Binding>().is_err());
+    assert!("Ctrl+Shift".parse::<Binding>().is_err());
+
+    let sequence = Sequence(vec!["Ctrl+K".parse().unwrap(), "Ctrl+C".parse().unwrap()]);
+    assert_eq!(sequence.to_string(), "Ctrl+K Ctrl+C");
+    assert_eq!(" Ctrl+K  Ctrl+C ".parse(), Ok(sequence));
+
+    assert_eq!("Hyper+A".parse::<Chord>(), Err(ParseBindingError { text: "Hyper+A".to_string() }));
+    assert!("Ctrl+".parse::<Chord>().is_err());
+    assert!("".parse::<Sequence>().is_err());
+}
+
+#[test]
+fn test_chord_bindings() {
+    use rebind::{BindingConfig, BindConsole, Binding, Modifiers};
+
+    let config = BindingConfig::<MenuAction>::parse("confirm = Ctrl+Return\ncancel = Return").unwrap();
+    let mut rebind = Builder::<MenuAction>::new(TEST_SIZE).build_rebind();
+    rebind.set_profile(config.get_profile()).unwrap();
+    let ctrl = Modifiers { ctrl: true, ..Default::default() };
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm),
+               Some(&ButtonTuple(Some(Binding::Chord(ctrl, Keyboard(Key::Return))), None, None)));
+    BindConsole::new().execute(&mut rebind, "bind alt+p +pause").unwrap();
+
+    // The chord is pressed instead of the key while exactly its modifiers are held
+    let mut translator = rebind.into_translator();
+    let mut translate = |input| translator.translate_stateful(&input);
+    assert_eq!(translate(Input::Press(Keyboard(Key::Return))), vec![Translated::Press(MenuAction::Cancel)]);
+    assert_eq!(translate(Input::Release(Keyboard(Key::Return))), vec![Translated::Release(MenuAction::Cancel)]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::RCtrl))), vec![]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::Return))), vec![Translated::Press(MenuAction::Confirm)]);
+    assert_eq!(translate(Input::Release(Keyboard(Key::RCtrl))), vec![]);
+    assert_eq!(translate(Input::Release(Keyboard(Key::Return))), vec![Translated::Release(MenuAction::Confirm)]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::LAlt))), vec![]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::LShift))), vec![]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::P))), vec![]);
+    assert_eq!(translate(Input::Release(Keyboard(Key::LShift))), vec![]);
+    assert_eq!(translate(Input::Press(Keyboard(Key::P))), vec![Translated::Press(MenuAction::Pause)]);
+}
+
+#[cfg(feature = "serde")]
+fn create_profile() -> rebind::Profile<MenuAction> {
+    use input::{Button, MouseButton};
+    use rebind::GamepadButton;
+
+    let builder = Builder::<MenuAction>::new(TEST_SIZE).with_mapping(MenuAction::Confirm, Keyboard(Key::Return))
+                                         .with_mapping(MenuAction::Confirm, GamepadButton::South)
+                                         .with_mapping(MenuAction::Pause, Button::Mouse(MouseButton::X1))
+                                         .y_motion_inverted(true)
+                                         .drag_threshold(Some(3.5))
+                                         .mouse_sensitivity(0.75);
+    builder.get_profile()
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_profile_json_round_trip() {
+    let profile = create_profile();
+    let json = serde_json::to_string(&profile).unwrap();
+    assert!(json.starts_with(r#"{"keymap":{"confirm":["Return","Gamepad.South"],"pause":["Mouse.X1"]},"#));
+    assert_eq!(serde_json::from_str::<rebind::Profile<MenuAction>>(&json).unwrap(), profile);
+
+    let edited = r#"{"keymap": {"cancel": ["Escape", "Ctrl+Q"]}, "mouse": {"invert_x_scroll": true}}"#;
+    let mut rebind = Builder::<MenuAction>::new(TEST_SIZE).build_rebind();
+    rebind.set_profile(&serde_json::from_str(edited).unwrap()).unwrap();
+    assert!(rebind.get_bindings(&MenuAction::Cancel).unwrap().contains(Keyboard(Key::Escape)));
+    let ctrl_q = rebind::Binding::Chord(rebind::Modifiers { ctrl: true, ..Default::default() }, Keyboard(Key::Q));
+    assert!(rebind.get_bindings(&MenuAction::Cancel).unwrap().contains(ctrl_q));
+    assert!(rebind.get_x_scroll_inverted());
+    assert_eq!(rebind.get_scroll_step(), 1.0);
+
+    assert!(serde_json::from_str::<rebind::Profile<MenuAction>>(r#"{"keymap": {"jump": ["Space"]}}"#).is_err());
+    assert!(serde_json::from_str::<rebind::Profile<MenuAction>>(r#"{"keymap": {"pause": ["Spcae"]}}"#).is_err());
+    let shared = r#"{"keymap": {"confirm": ["Return"], "cancel": ["Escape", "Return"]}}"#;
+    assert!(serde_json::from_str::<rebind::Profile<MenuAction>>(shared).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_profile_toml_round_trip() {
+    let profile = create_profile();
+    let text = toml::to_string(&profile).unwrap();
+    assert!(text.contains(r#"confirm = ["Return", "Gamepad.South"]"#));
+    assert!(text.contains("invert_y_motion = true"));
+    assert_eq!(toml::from_str::<rebind::Profile<MenuAction>>(&text).unwrap(), profile);
+
+    let translator = Builder::<MenuAction>::new(TEST_SIZE).profile(toml::from_str(&text).unwrap())
+                                                          .unwrap()
+                                                          .build_translator();
+    assert_eq!(translator.translate(&Input::Press(Keyboard(Key::Return))),
+               Some(Translated::Press(MenuAction::Confirm)));
+}
+
+#[test]
+fn test_profile_rejects_shared_bindings() {
+    use rebind::{Binding, Profile, ProfileError};
+
+    let ret = || Some(Binding::from(Keyboard(Key::Return)));
+    let mut profile = Profile::default();
+    profile.keymap = vec![(MenuAction::Confirm, ButtonTuple(ret(), None, None)),
+                          (MenuAction::Cancel, ButtonTuple(Some(Keyboard(Key::Escape).into()), ret(), None))];
+    let error = ProfileError::DuplicateBinding(Keyboard(Key::Return).into(), MenuAction::Confirm, MenuAction::Cancel);
+    assert_eq!(profile.check(), Err(error));
+    assert_eq!(error.to_string(), "Return is bound to both Confirm and Cancel");
+
+    let mut rebind = Builder::<MenuAction>::new(TEST_SIZE).with_mapping(MenuAction::Pause, Keyboard(Key::P))
+                                                          .build_rebind();
+    assert_eq!(rebind.set_profile(&profile), Err(error));
+    assert!(rebind.get_bindings(&MenuAction::Pause).unwrap().contains(Keyboard(Key::P)));
+    assert_eq!(Builder::<MenuAction>::new(TEST_SIZE).profile(profile).err(), Some(error));
+}
+
+#[test]
+fn test_binding_config() {
+    use input::{Button, MouseButton};
+    use rebind::{BindingConfig, BindingConfigError, BindingConfigErrorKind, GamepadButton};
+
+    let text = "# Menu controls\n\
+                confirm = Return, Gamepad.South  # the A button\n\
+                \n\
+                [mouse]\n\
+                invert_y_motion = true\n";
+    let mut config = BindingConfig::<MenuAction>::parse(text).unwrap();
+    let mut rebind = Builder::<MenuAction>::new(TEST_SIZE).profile(config.get_profile().clone())
+                                                          .unwrap()
+                                                          .build_rebind();
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm),
+               Some(&ButtonTuple(Some(Keyboard(Key::Return).into()), Some(GamepadButton::South.into()), None)));
+    assert!(rebind.get_y_motion_inverted());
+    assert_eq!(config.to_string(), text);
+
+    rebind.insert_action_with_buttons(MenuAction::Confirm, ButtonTuple(Some(Keyboard(Key::Space)), None, None));
+    rebind.insert_action_with_buttons(MenuAction::Pause, ButtonTuple(Some(Button::Mouse(MouseButton::X1)), None, None));
+    rebind.set_y_motion_inverted(false);
+    rebind.set_scroll_step(2.0);
+    config.set_profile(rebind.get_profile()).unwrap();
+    assert_eq!(config.to_string(),
+               "# Menu controls\n\
+                confirm = Space  # the A button\n\
+                pause = Mouse.X1\n\
+                \n\
+                [mouse]\n\
+                invert_y_motion = false\n\
+                scroll_step = 2\n");
+
+    let error = |text| BindingConfig::<MenuAction>::parse(text).unwrap_err();
+    assert_eq!(error("confirm = Return\n  cancel = Escape, Spcae"),
+               BindingConfigError {
+                   line: 2,
+                   column: 20,
+                   kind: BindingConfigErrorKind::UnknownBinding("Spcae".to_string()),
+                   suggestion: Some("Space".to_string())
+               });
+    assert_eq!(error("confrim = Return").to_string(),
+               "line 1, column 1: unknown action `confrim` (did you mean `confirm`?)");
+    assert_eq!(error("[mouse]\ninvert_y_motion = yes").to_string(), "line 2, column 19: invalid value `yes`");
+    assert_eq!(error("[mouse]\ninvert_y_moton = true").suggestion, Some("invert_y_motion".to_string()));
+    assert_eq!(error("pause = A, B, C, D").kind, BindingConfigErrorKind::TooManyBindings);
+    assert_eq!(error("pause").kind, BindingConfigErrorKind::MissingEquals);
+    assert_eq!(error("confirm = Return\ncancel = Escape, return").to_string(),
+               "line 2, column 18: `return` is already bound to `confirm`");
+    assert_eq!(error("pause = P, P").kind,
+               BindingConfigErrorKind::DuplicateBinding("P".to_string(), "pause".to_string()));
+}
+
+#[test]
+fn test_bind_console() {
+    use input::{Button, MouseButton};
+    use rebind::{BindConsole, Binding, ConsoleError};
+
+    let scripts = |name: &str| match name {
+        "binds.cfg" => Some("unbindall\nbind return +confirm // enter\nbind escape +cancel; bind p +pause".to_string()),
+        "loop.cfg" => Some("exec loop.cfg".to_string()),
+        "broken.cfg" => Some("bind space +confirm\nbind space +jump".to_string()),
+        _ => None,
+    };
+    let mut rebind = Builder::<MenuAction>::new((800, 600)).build_rebind();
+    rebind.insert_action_with_buttons(MenuAction::Pause, ButtonTuple(Some(Keyboard(Key::Space)), None, None));
+    let mut console = BindConsole::new().script_loader(scripts);
+
+    assert!(console.execute(&mut rebind, "exec binds.cfg").unwrap().lines.is_empty());
+    let key = |k| ButtonTuple(Some(Binding::Button(Keyboard(k))), None, None);
+    assert_eq!(rebind.get_all_bindings(),
+               vec![(MenuAction::Confirm, key(Key::Return)),
+                    (MenuAction::Cancel, key(Key::Escape)),
+                    (MenuAction::Pause, key(Key::P))]);
+
+    // Binding a button which is bound to another action moves it
+    console.execute(&mut rebind, "bind \"Mouse.Left\" +confirm; bind p +confirm").unwrap();
+    assert_eq!(console.execute(&mut rebind, "bind P").unwrap().lines, vec!["\"P\" = \"+confirm\"".to_string()]);
+    assert_eq!(console.execute(&mut rebind, "bind space +confirm"),
+               Err(ConsoleError::TooManyBindings("confirm".to_string())));
+    console.execute(&mut rebind, "unbind p").unwrap();
+    assert_eq!(console.execute(&mut rebind, "bindlist").unwrap().lines,
+               vec!["Return \"+confirm\"".to_string(),
+                    "Mouse.Left \"+confirm\"".to_string(),
+                    "Escape \"+cancel\"".to_string()]);
+
+    // Held commands press an action until it's released
+    let output = console.execute(&mut rebind, "+confirm; +confirm; -cancel").unwrap();
+    assert_eq!(output.actions, vec![Translated::Press(MenuAction::Confirm)]);
+    assert_eq!(console.get_held_actions(), &[MenuAction::Confirm]);
+    let output = console.execute(&mut rebind, "-confirm").unwrap();
+    assert_eq!(output.actions, vec![Translated::Release(MenuAction::Confirm)]);
+
+    // A line which fails has no effect
+    assert_eq!(console.execute(&mut rebind, "+confirm; unbind return; bogus"),
+               Err(ConsoleError::UnknownCommand("bogus".to_string())));
+    assert!(console.get_held_actions().is_empty());
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm).unwrap().0, key(Key::Return).0);
+
+    // Without a `+`, a button is bound to a command which is run once when it's pressed
+    console.execute(&mut rebind, "bind c confirm").unwrap();
+    assert_eq!(console.execute(&mut rebind, "bind c").unwrap().lines, vec!["\"C\" = \"confirm\"".to_string()]);
+    let c = Binding::Button(Keyboard(Key::C));
+    assert_eq!(console.run_bound_command(&mut rebind, c).unwrap().actions,
+               vec![Translated::Press(MenuAction::Confirm), Translated::Release(MenuAction::Confirm)]);
+    assert!(console.get_held_actions().is_empty());
+    console.execute(&mut rebind, "unbind c").unwrap();
+    assert_eq!(console.get_bound_command(c), None);
+
+    // The exported script restores the bindings
+    let script = console.bind_script(&rebind);
+    assert_eq!(script,
+               "unbindall\nbind Return +confirm\nbind Mouse.Left +confirm\nbind Escape +cancel\n");
+    let expected = rebind.get_all_bindings();
+    console.execute(&mut rebind, "unbindall").unwrap();
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm), Some(&ButtonTuple::new()));
+    for line in script.lines() {
+        console.execute(&mut rebind, line).unwrap();
+    }
+    assert_eq!(rebind.get_all_bindings(), expected);
+    let left_click = Binding::Button(Button::Mouse(MouseButton::Left));
+    assert!(rebind.get_bindings(&MenuAction::Confirm).unwrap().contains(left_click));
+
+    assert_eq!(console.execute(&mut rebind, "bnid w +confirm"),
+               Err(ConsoleError::UnknownCommand("bnid".to_string())));
+    assert_eq!(console.execute(&mut rebind, "unbind"), Err(ConsoleError::Usage("unbind <button>")));
+    assert_eq!(console.execute(&mut rebind, "exec missing.cfg"),
+               Err(ConsoleError::ScriptNotFound("missing.cfg".to_string())));
+    assert_eq!(console.execute(&mut rebind, "exec loop.cfg"),
+               Err(ConsoleError::Script { name: "loop.cfg".to_string(),
+                                          line: 1,
+                                          error: Box::new(ConsoleError::RecursiveExec("loop.cfg".to_string())) }));
+    assert_eq!(console.execute(&mut rebind, "exec broken.cfg").unwrap_err().to_string(),
+               "broken.cfg, line 2: unknown action `jump`");
+    assert!(!rebind.get_bindings(&MenuAction::Confirm).unwrap().contains(Binding::Button(Keyboard(Key::Space))));
+
+    // Locked actions keep their buttons
+    console.execute(&mut rebind, "bind p +pause").unwrap();
+    let mut console = console.lock_actions();
+    console.execute(&mut rebind, "bind p +pause").unwrap();
+    assert_eq!(console.execute(&mut rebind, "bind q +pause"), Err(ConsoleError::Locked("pause".to_string())));
+    assert_eq!(console.execute(&mut rebind, "bind p +confirm"),
+               Err(ConsoleError::Conflict("P".to_string(), "pause".to_string())));
+    assert_eq!(console.execute(&mut rebind, "unbind p").unwrap_err().to_string(),
+               "`P` is bound to `pause`, which can't be rebound");
+    console.execute(&mut rebind, "unbindall").unwrap();
+    assert_eq!(rebind.get_bindings(&MenuAction::Pause), Some(&key(Key::P)));
+    assert_eq!(rebind.get_bindings(&MenuAction::Confirm), Some(&ButtonTuple::new()));
+}
+
+#[test]
+fn test_button_names() {
+    use input::{Button, ControllerButton, MouseButton};
+    use rebind::{Binding, ButtonNames, GamepadButton, NameForm, ScrollDirection};
+    use std::collections::HashMap;
+
+    let names = ButtonNames::new();
+    let left_click = Button::Mouse(MouseButton::Left);
+    assert_eq!(names.button_name(left_click, NameForm::Short), "LMB");
+    assert_eq!(names.button_name(left_click, NameForm::Long), "Left Mouse Button");
+    assert_eq!(names.button_name(Keyboard(Key::D1), NameForm::Long), "1");
+    assert_eq!(names.button_name(Keyboard(Key::F1), NameForm::Long), "F1");
+    assert_eq!(names.button_name(Button::Controller(ControllerButton { id: 1, button: 4 }), NameForm::Long),
+               "Controller 1 Button 4");
+    assert_eq!(names.binding_name(Binding::DoubleClick(MouseButton::Right), NameForm::Short), "2x RMB");
+    assert_eq!(names.binding_name(Binding::Scroll(ScrollDirection::Up), NameForm::Long), "Mouse Wheel Up");
+    assert_eq!(names.chord_name(&"Ctrl+Shift+Esc".parse().unwrap(), NameForm::Long), "Ctrl + Shift + Escape");
+    assert_eq!(names.sequence_name(&"Ctrl+K Ctrl+C".parse().unwrap(), NameForm::Short), "Ctrl+K Ctrl+C");
+
+    // Names missing from the string table are shown in English
+    let mut german = HashMap::new();
+    german.insert("mouse.left".to_string(), "Linke Maustaste".to_string());
+    german.insert("mouse.right".to_string(), "Rechte Maustaste".to_string());
+    german.insert("mouse.right.short".to_string(), "RMT".to_string());
+    german.insert("modifier.ctrl".to_string(), "Strg".to_string());
+    let names = ButtonNames::new().string_table(german);
+    assert_eq!(names.button_name(left_click, NameForm::Short), "Linke Maustaste");
+    assert_eq!(names.button_name(Button::Mouse(MouseButton::Right), NameForm::Short), "RMT");
+    assert_eq!(names.button_name(Button::Mouse(MouseButton::Middle), NameForm::Short), "MMB");
+    assert_eq!(names.chord_name(&"Ctrl+Mouse.Left".parse().unwrap(), NameForm::Short), "Strg+Linke Maustaste");
+
+    assert_eq!(names.binding_name(Binding::Gamepad(GamepadButton::South), NameForm::Long), "South Face Button");
+    assert_eq!(GamepadButton::South.glyph_id(), "face_south");
+    assert_eq!(GamepadButton::DPadLeft.glyph_id(), "dpad_left");
+
+    // Names can be made on another thread, such as one which loads the menus
+    fn assert_send<T: Send + Sync>(_: T) { }
+    assert_send(names);
+}